@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fs;
+use std::time::Duration;
+use toml::{Table, Value};
+use crate::solutions::Solution;
+
+pub const ANSWERS_PATH: &str = "answers.toml";
+
+// Known answers for our puzzle inputs, one table per day:
+//
+//   [day17]
+//   part1 = "6,5,4,7,1,6,0,3,1"
+//   part2 = 106086382266778
+#[derive(Debug, Default)]
+pub struct Answers {
+    values: HashMap<(u32, u32), String>,
+}
+
+fn key_number(key: &str, prefix: &str, max: u32) -> Option<u32> {
+    key.strip_prefix(prefix)?
+        .parse()
+        .ok()
+        .filter(|n| *n >= 1 && *n <= max)
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Table = text.parse().map_err(|e| format!("{e}"))?;
+        let mut values = HashMap::new();
+
+        for (day_key, parts) in &table {
+            let day = key_number(day_key, "day", 25)
+                .ok_or_else(|| format!("unknown table `{day_key}`, expected `day<1-25>`"))?;
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| format!("`{day_key}` should be a table"))?;
+
+            for (part_key, value) in parts {
+                let part = key_number(part_key, "part", 2)
+                    .ok_or_else(|| format!("unknown key `{day_key}.{part_key}`, expected `part1` or `part2`"))?;
+
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Integer(n) => n.to_string(),
+                    _ => return Err(format!("`{day_key}.{part_key}` should be a string or an integer")),
+                };
+                values.insert((day, part), value);
+            }
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+        Self::parse(&text).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        self.values.get(&(day, part)).map(|s| s.as_str())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    Pass,
+    Fail { expected: String, found: String },
+    Missing,
+    Error(String),
+}
+
+// Runs the solution and compares its answer with the registered one. The
+// parts without an answer aren't run, some of them are still `todo!()`
+pub fn check(answers: &Answers, solution: &Solution, input: &str) -> (Check, Duration) {
+    let Some(expected) = answers.get(solution.day, solution.part) else {
+        return (Check::Missing, Duration::ZERO);
+    };
+
+    match solution.run(input) {
+        Ok(answer) => {
+            let time = answer.generator + answer.runner;
+            let check = if expected == answer.value {
+                Check::Pass
+            } else {
+                Check::Fail { expected: expected.to_string(), found: answer.value }
+            };
+            (check, time)
+        }
+        Err(e) => (Check::Error(e), Duration::ZERO),
+    }
+}
+
+// Line by line diff, `-` for the expected lines and `+` for the found ones
+pub fn diff(expected: &str, found: &str) -> Vec<String> {
+    let expected: Vec<_> = expected.lines().collect();
+    let found: Vec<_> = found.lines().collect();
+    let mut result = Vec::new();
+
+    for i in 0 .. expected.len().max(found.len()) {
+        let e = expected.get(i);
+        let f = found.get(i);
+        if e == f {
+            continue;
+        }
+        if let Some(e) = e {
+            result.push(format!("- {e}"));
+        }
+        if let Some(f) = f {
+            result.push(format!("+ {f}"));
+        }
+    }
+    result
+}
+
+
+#[test]
+fn test_parse() {
+    let answers = Answers::parse(
+        "[day1]
+         part1 = 11
+         part2 = \"31\"
+
+         [day17]
+         part1 = \"4,6,3,5,6,3,5,2,1,0\""
+    ).unwrap();
+
+    assert_eq!(answers.get(1, 1), Some("11"));
+    assert_eq!(answers.get(1, 2), Some("31"));
+    assert_eq!(answers.get(17, 1), Some("4,6,3,5,6,3,5,2,1,0"));
+    assert_eq!(answers.get(17, 2), None);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        Answers::parse("[day26]\npart1 = 1").unwrap_err(),
+        "unknown table `day26`, expected `day<1-25>`"
+    );
+    assert_eq!(
+        Answers::parse("[day2]\npart3 = 1").unwrap_err(),
+        "unknown key `day2.part3`, expected `part1` or `part2`"
+    );
+    assert_eq!(
+        Answers::parse("[day2]\npart1 = 1.5").unwrap_err(),
+        "`day2.part1` should be a string or an integer"
+    );
+}
+
+#[test]
+fn test_check() {
+    let answers = Answers::parse("[day1]\npart1 = 11\npart2 = 30").unwrap();
+    let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3";
+
+    let (result, _) = check(&answers, &Solution::find(1, 1).unwrap(), input);
+    assert_eq!(result, Check::Pass);
+
+    let (result, _) = check(&answers, &Solution::find(1, 2).unwrap(), input);
+    assert_eq!(result, Check::Fail { expected: String::from("30"), found: String::from("31") });
+
+    let (result, _) = check(&Answers::default(), &Solution::find(1, 2).unwrap(), input);
+    assert_eq!(result, Check::Missing);
+
+    // Never run, it would panic on the `todo!()`
+    let (result, _) = check(&answers, &Solution::find(25, 2).unwrap(), input);
+    assert_eq!(result, Check::Missing);
+}
+
+#[test]
+fn test_diff() {
+    assert_eq!(diff("30", "31"), vec!["- 30", "+ 31"]);
+    assert_eq!(diff("a\nb\nc", "a\nx"), vec!["- b", "+ x", "- c"]);
+    assert!(diff("same", "same").is_empty());
+}
@@ -0,0 +1,235 @@
+use std::fs;
+use std::time::Duration;
+use serde_json::{json, Value};
+use crate::solutions::Solution;
+
+pub const REPORT_PATH: &str = "bench.json";
+
+// Summary of the times of several iterations
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl Stats {
+    pub fn new(times: &[Duration]) -> Self {
+        let mut times = times.to_vec();
+        times.sort();
+
+        // Nearest rank, the smallest time with at least `p` of them below
+        let rank = |p: f64| times[((p * times.len() as f64).ceil() as usize).max(1) - 1];
+        Self { min: times[0], median: rank(0.5), p95: rank(0.95) }
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "min_ns": self.min.as_nanos() as u64,
+            "median_ns": self.median.as_nanos() as u64,
+            "p95_ns": self.p95.as_nanos() as u64,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let nanos = |key| value.get(key)?.as_u64().map(Duration::from_nanos);
+        Some(Self { min: nanos("min_ns")?, median: nanos("median_ns")?, p95: nanos("p95_ns")? })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Timing {
+    pub day: u32,
+    pub part: u32,
+    pub generator: Stats,
+    pub runner: Stats,
+}
+
+// Runs the solution `iterations` times timing the generator and the part
+pub fn bench(solution: &Solution, input: &str, iterations: u32) -> Result<Timing, String> {
+    let mut generator = Vec::new();
+    let mut runner = Vec::new();
+
+    for _ in 0 .. iterations.max(1) {
+        let answer = solution.run(input)?;
+        generator.push(answer.generator);
+        runner.push(answer.runner);
+    }
+
+    Ok(Timing {
+        day: solution.day,
+        part: solution.part,
+        generator: Stats::new(&generator),
+        runner: Stats::new(&runner),
+    })
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Report {
+    pub iterations: u32,
+    pub timings: Vec<Timing>,
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        let timings: Vec<Value> = self.timings
+            .iter()
+            .map(|t| json!({
+                "day": t.day,
+                "part": t.part,
+                "generator": t.generator.to_json(),
+                "runner": t.runner.to_json(),
+            }))
+            .collect();
+
+        let report = json!({ "iterations": self.iterations, "timings": timings });
+        serde_json::to_string_pretty(&report).unwrap()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| format!("{e}"))?;
+        let invalid = || String::from("not a benchmark report");
+
+        let iterations = value.get("iterations").and_then(Value::as_u64).ok_or_else(invalid)? as u32;
+        let timings = value
+            .get("timings")
+            .and_then(Value::as_array)
+            .ok_or_else(invalid)?
+            .iter()
+            .map(|t| Some(Timing {
+                day: t.get("day")?.as_u64()? as u32,
+                part: t.get("part")?.as_u64()? as u32,
+                generator: Stats::from_json(t.get("generator")?)?,
+                runner: Stats::from_json(t.get("runner")?)?,
+            }))
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?;
+
+        Ok(Self { iterations, timings })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))?;
+        Self::parse(&text).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_json() + "\n").map_err(|e| format!("writing {path}: {e}"))
+    }
+
+    fn get(&self, day: u32, part: u32) -> Option<&Timing> {
+        self.timings.iter().find(|t| t.day == day && t.part == part)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Regression {
+    pub day: u32,
+    pub part: u32,
+    pub phase: &'static str,
+    pub before: Duration,
+    pub after: Duration,
+}
+
+// Phases whose median got more than `threshold` percent slower. Times under
+// `floor` are ignored, they are mostly noise.
+pub fn compare(before: &Report, after: &Report, threshold: u32, floor: Duration) -> Vec<Regression> {
+    let mut result = Vec::new();
+
+    for timing in &after.timings {
+        let Some(previous) = before.get(timing.day, timing.part) else {
+            continue;
+        };
+
+        let phases = [
+            ("generator", previous.generator.median, timing.generator.median),
+            ("runner", previous.runner.median, timing.runner.median),
+        ];
+        for (phase, before, after) in phases {
+            if after >= floor && after.as_nanos() * 100 > before.as_nanos() * (100 + threshold as u128) {
+                result.push(Regression { day: timing.day, part: timing.part, phase, before, after });
+            }
+        }
+    }
+    result
+}
+
+
+#[cfg(test)]
+fn millis(ms: &[u64]) -> Vec<Duration> {
+    ms.iter().map(|ms| Duration::from_millis(*ms)).collect()
+}
+
+#[cfg(test)]
+fn timing(day: u32, part: u32, generator: u64, runner: u64) -> Timing {
+    Timing {
+        day,
+        part,
+        generator: Stats::new(&millis(&[generator])),
+        runner: Stats::new(&millis(&[runner])),
+    }
+}
+
+#[test]
+fn test_stats() {
+    let stats = Stats::new(&millis(&[5, 1, 4, 2, 3]));
+    assert_eq!(stats, Stats {
+        min: Duration::from_millis(1),
+        median: Duration::from_millis(3),
+        p95: Duration::from_millis(5),
+    });
+
+    let times: Vec<u64> = (1 ..= 100).collect();
+    let stats = Stats::new(&millis(&times));
+    assert_eq!(stats.median, Duration::from_millis(50));
+    assert_eq!(stats.p95, Duration::from_millis(95));
+}
+
+#[test]
+fn test_bench() {
+    let solution = Solution::find(1, 1).unwrap();
+    let result = bench(&solution, "3   4\n4   3\n2   5", 3).unwrap();
+    assert_eq!((result.day, result.part), (1, 1));
+    assert!(result.runner.min <= result.runner.p95);
+
+    let err = bench(&solution, "3   x", 3).unwrap_err();
+    assert!(err.starts_with("generating: "));
+}
+
+#[test]
+fn test_report_json() {
+    let report = Report { iterations: 10, timings: vec![timing(1, 1, 3, 20), timing(17, 2, 1, 5)] };
+    assert_eq!(Report::parse(&report.to_json()), Ok(report));
+
+    assert_eq!(Report::parse("{\"timings\": []}"), Err(String::from("not a benchmark report")));
+    assert!(Report::parse("{").is_err());
+}
+
+#[test]
+fn test_compare() {
+    let before = Report { iterations: 10, timings: vec![timing(1, 1, 10, 100), timing(2, 1, 10, 100)] };
+    let after = Report {
+        iterations: 10,
+        timings: vec![timing(1, 1, 11, 150), timing(2, 1, 30, 100), timing(3, 1, 10, 10)],
+    };
+
+    assert_eq!(compare(&before, &after, 20, Duration::ZERO), vec![
+        Regression {
+            day: 1,
+            part: 1,
+            phase: "runner",
+            before: Duration::from_millis(100),
+            after: Duration::from_millis(150),
+        },
+        Regression {
+            day: 2,
+            part: 1,
+            phase: "generator",
+            before: Duration::from_millis(10),
+            after: Duration::from_millis(30),
+        },
+    ]);
+
+    // The generator of day 2 is under the floor
+    assert_eq!(compare(&before, &after, 20, Duration::from_millis(50)).len(), 1);
+}
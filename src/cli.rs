@@ -0,0 +1,713 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use crate::answers::{self, Answers, Check};
+use crate::bench::{self, Report};
+use crate::export::Recorder;
+use crate::{day14, day15, day17, day6};
+use crate::parallel;
+use crate::solutions::{self, Answer, Solution};
+
+const USAGE: &str = "Usage:
+  aoc-2024 run --day <1-25> [--part <1-2>] [--input <path>]
+  aoc-2024 run --all [--part <1-2>] [--jobs <n>]
+  aoc-2024 verify [--day <1-25>] [--part <1-2>] [--answers <path>]
+  aoc-2024 bench [--day <1-25>] [--part <1-2>] [--iterations <n>]
+                 [--output <path>] [--baseline <path>] [--threshold <percent>]
+  aoc-2024 play --day 15 [--double | --scale <h>x<w>] [--save <path>]
+  aoc-2024 debug --day 17 [--input <path>]
+  aoc-2024 record --day <6|14|15> [--input <path>] [--format <gif|png>]
+                  [--output <path>]
+
+Inputs default to input/2024/day<n>.txt, `--input -` reads stdin.
+Without arguments runs every solution. With --jobs the solutions run in
+parallel, quiet, and a summary sorted by time is printed at the end.
+verify compares the answers with the ones registered in answers.toml.
+bench times each generator and part with a registered answer over 10
+iterations by default and writes the report to bench.json; the medians
+are compared with the baseline, by default the previous report, and any
+that got slower than the threshold (10%) is flagged. play moves the day
+15 robot with the keyboard, on the part 2 warehouse with --double or
+with every cell made h x w cells with --scale, and writes the map and
+the moves played to --save as a puzzle input.
+debug runs the day 17 program step by step from a prompt, `help` lists
+the commands.
+record draws the steps of day 6, 14 or 15 as an animated GIF, by
+default day<n>.gif, or as PNG frames in a directory, by default
+day<n>-frames.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    // `day` is None when running all of them
+    Run { day: Option<u32>, part: Option<u32>, input: Option<String>, jobs: Option<u32> },
+    Verify { day: Option<u32>, part: Option<u32>, answers: Option<String> },
+    Bench(BenchOptions),
+    // Height and width of each cell of the map
+    Play { day: u32, scale: (usize, usize), save: Option<String> },
+    Debug { day: u32, input: Option<String> },
+    Record { day: u32, input: Option<String>, format: Format, output: Option<String> },
+    Help,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    Gif,
+    Png,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BenchOptions {
+    pub day: Option<u32>,
+    pub part: Option<u32>,
+    pub iterations: u32,
+    pub output: String,
+    pub baseline: Option<String>,
+    pub threshold: u32,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            day: None,
+            part: None,
+            iterations: 10,
+            output: String::from(bench::REPORT_PATH),
+            baseline: None,
+            threshold: 10,
+        }
+    }
+}
+
+// Times under this are too noisy to report regressions
+const BENCH_FLOOR: Duration = Duration::from_micros(100);
+
+// Days with a recorder, pixels per cell and time per frame of the GIFs
+const RECORD_DAYS: [u32; 3] = [6, 14, 15];
+const RECORD_CELL: usize = 4;
+const RECORD_DELAY: Duration = Duration::from_millis(100);
+
+fn flag_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("missing value for {flag}"))
+}
+
+fn flag_number(args: &mut impl Iterator<Item = String>, flag: &str, max: u32) -> Result<u32, String> {
+    let value = flag_value(args, flag)?;
+    match value.parse() {
+        Ok(n) if n >= 1 && n <= max => Ok(n),
+        _ => Err(format!("invalid value for {flag}: `{value}`, expected 1 to {max}")),
+    }
+}
+
+fn parse_run(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut jobs = None;
+    let mut all = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(flag_number(&mut args, &arg, 25)?),
+            "--part" | "-p" => part = Some(flag_number(&mut args, &arg, 2)?),
+            "--input" | "-i" => input = Some(flag_value(&mut args, &arg)?),
+            "--jobs" | "-j" => jobs = Some(flag_number(&mut args, &arg, 256)?),
+            "--all" => all = true,
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    match (day, all) {
+        (Some(_), true) => Err(String::from("--day and --all can't be used together")),
+        (None, false) => Err(String::from("run needs --day or --all")),
+        (None, true) if input.is_some() => Err(String::from("--input needs --day")),
+        (Some(_), _) if jobs.is_some() => Err(String::from("--jobs needs --all")),
+        _ => Ok(Command::Run { day, part, input, jobs }),
+    }
+}
+
+fn parse_verify(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut day = None;
+    let mut part = None;
+    let mut answers = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(flag_number(&mut args, &arg, 25)?),
+            "--part" | "-p" => part = Some(flag_number(&mut args, &arg, 2)?),
+            "--answers" | "-a" => answers = Some(flag_value(&mut args, &arg)?),
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    Ok(Command::Verify { day, part, answers })
+}
+
+fn parse_bench(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut options = BenchOptions::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "-d" => options.day = Some(flag_number(&mut args, &arg, 25)?),
+            "--part" | "-p" => options.part = Some(flag_number(&mut args, &arg, 2)?),
+            "--iterations" | "-n" => options.iterations = flag_number(&mut args, &arg, 100_000)?,
+            "--output" | "-o" => options.output = flag_value(&mut args, &arg)?,
+            "--baseline" | "-b" => options.baseline = Some(flag_value(&mut args, &arg)?),
+            "--threshold" | "-t" => options.threshold = flag_number(&mut args, &arg, 1000)?,
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    Ok(Command::Bench(options))
+}
+
+// Largest height or width of --scale
+const MAX_SCALE: usize = 10;
+
+fn flag_scale(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<(usize, usize), String> {
+    let value = flag_value(args, flag)?;
+    let size = |n: &str| n.parse().ok().filter(|n| (1 ..= MAX_SCALE).contains(n));
+    value
+        .split_once('x')
+        .and_then(|(height, width)| Some((size(height)?, size(width)?)))
+        .ok_or_else(|| format!("invalid value for {flag}: `{value}`, expected <h>x<w> with 1 to {MAX_SCALE}"))
+}
+
+fn parse_play(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut day = None;
+    let mut scale = (1, 1);
+    let mut save = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(flag_number(&mut args, &arg, 25)?),
+            "--double" => scale = (1, 2),
+            "--scale" => scale = flag_scale(&mut args, &arg)?,
+            "--save" => save = Some(flag_value(&mut args, &arg)?),
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    match day {
+        None => Err(String::from("play needs --day")),
+        Some(15) => Ok(Command::Play { day: 15, scale, save }),
+        Some(day) => Err(format!("day {day} can't be played, only day 15")),
+    }
+}
+
+fn parse_debug(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut day = None;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(flag_number(&mut args, &arg, 25)?),
+            "--input" | "-i" => input = Some(flag_value(&mut args, &arg)?),
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    match day {
+        None => Err(String::from("debug needs --day")),
+        // The commands are read from stdin
+        Some(_) if input.as_deref() == Some("-") => Err(String::from("debug can't read the input from stdin")),
+        Some(17) => Ok(Command::Debug { day: 17, input }),
+        Some(day) => Err(format!("day {day} can't be debugged, only day 17")),
+    }
+}
+
+fn parse_record(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut day = None;
+    let mut input = None;
+    let mut format = Format::Gif;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(flag_number(&mut args, &arg, 25)?),
+            "--input" | "-i" => input = Some(flag_value(&mut args, &arg)?),
+            "--format" | "-f" => {
+                format = match flag_value(&mut args, &arg)?.as_str() {
+                    "gif" => Format::Gif,
+                    "png" => Format::Png,
+                    other => return Err(format!("invalid value for {arg}: `{other}`, expected gif or png")),
+                }
+            }
+            "--output" | "-o" => output = Some(flag_value(&mut args, &arg)?),
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    match day {
+        None => Err(String::from("record needs --day")),
+        Some(day) if RECORD_DAYS.contains(&day) => Ok(Command::Record { day, input, format, output }),
+        Some(day) => Err(format!("day {day} can't be recorded, only days 6, 14 and 15")),
+    }
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+
+    match args.next().as_deref() {
+        None => Ok(Command::Run { day: None, part: None, input: None, jobs: None }),
+        Some("run") => parse_run(args),
+        Some("verify") => parse_verify(args),
+        Some("bench") => parse_bench(args),
+        Some("play") => parse_play(args),
+        Some("debug") => parse_debug(args),
+        Some("record") => parse_record(args),
+        Some("help" | "--help" | "-h") => Ok(Command::Help),
+        Some(other) => Err(format!("unknown command `{other}`")),
+    }
+}
+
+fn print_answer(answer: &Answer) {
+    println!("Day {} - Part {}: {}\n\tgenerator: {:?},\n\trunner: {:?}\n",
+             answer.day, answer.part, answer.value, answer.generator, answer.runner);
+}
+
+fn select(day: Option<u32>, part: Option<u32>) -> Vec<Solution> {
+    solutions::all()
+        .into_iter()
+        .filter(|s| day.is_none_or(|d| s.day == d) && part.is_none_or(|p| s.part == p))
+        .collect()
+}
+
+// Reads each input once, stdin can't be read again for the second part
+struct Inputs {
+    path: Option<String>,
+    texts: HashMap<u32, Result<String, String>>,
+}
+
+impl Inputs {
+    fn new(path: Option<String>) -> Self {
+        Self { path, texts: HashMap::new() }
+    }
+
+    fn get(&mut self, day: u32) -> Result<&str, String> {
+        let path = &self.path;
+        let text = self.texts.entry(day).or_insert_with(|| {
+            let path = path.clone().unwrap_or_else(|| solutions::input_path(day));
+            solutions::read_input(&path)
+        });
+
+        match text {
+            Ok(text) => Ok(text),
+            Err(e) => Err(e.clone()),
+        }
+    }
+}
+
+fn run(day: Option<u32>, part: Option<u32>, input: Option<String>) -> ExitCode {
+    let mut inputs = Inputs::new(input);
+    let mut total = Duration::ZERO;
+    let mut failed = false;
+
+    for solution in select(day, part) {
+        let result = inputs.get(solution.day).and_then(|text| solution.run(text));
+
+        match result {
+            Ok(answer) => {
+                total += answer.generator + answer.runner;
+                print_answer(&answer);
+            }
+            Err(e) => {
+                eprintln!("Day {} - Part {}: FAILED while {e}\n", solution.day, solution.part);
+                failed = true;
+            }
+        }
+    }
+
+    if day.is_none() {
+        println!("Total: {total:?}");
+    }
+
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+fn run_parallel(part: Option<u32>, jobs: u32) -> ExitCode {
+    let solutions = select(None, part);
+
+    // Read up front, the workers only share the texts
+    let mut inputs = Inputs::new(None);
+    for solution in &solutions {
+        let _ = inputs.get(solution.day);
+    }
+
+    let start = Instant::now();
+    let mut outcomes = parallel::run(&solutions, &inputs.texts, jobs as usize);
+    let wall = start.elapsed();
+
+    outcomes.sort_by_key(|o| Reverse(o.time()));
+
+    println!("{:<4} {:<5} {:<48} {:>12} {:>12} {:>12}", "Day", "Part", "Answer", "generator", "runner", "total");
+    let mut failed = false;
+    for outcome in &outcomes {
+        match &outcome.result {
+            Ok(answer) => println!("{:<4} {:<5} {:<48} {:>12} {:>12} {:>12}",
+                                   outcome.day, outcome.part, answer.value,
+                                   format!("{:.2?}", answer.generator),
+                                   format!("{:.2?}", answer.runner),
+                                   format!("{:.2?}", outcome.time())),
+            Err(e) => {
+                println!("{:<4} {:<5} FAILED while {e}", outcome.day, outcome.part);
+                failed = true;
+            }
+        }
+    }
+
+    let total: Duration = outcomes.iter().map(|o| o.time()).sum();
+    println!("\nTotal: {total:?}, wall clock: {wall:?} with {jobs} jobs");
+
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+fn verify(day: Option<u32>, part: Option<u32>, path: Option<String>) -> ExitCode {
+    let path = path.unwrap_or_else(|| String::from(answers::ANSWERS_PATH));
+    let answers = match Answers::load(&path) {
+        Ok(answers) => answers,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let _quiet = parallel::Quiet::new();
+    let mut inputs = Inputs::new(None);
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    for solution in select(day, part) {
+        let name = format!("Day {} - Part {}", solution.day, solution.part);
+        let (check, time) = match inputs.get(solution.day) {
+            Ok(text) => answers::check(&answers, &solution, text),
+            Err(e) => (Check::Error(e), Duration::ZERO),
+        };
+
+        match check {
+            Check::Pass => {
+                passed += 1;
+                println!("{name}: ok ({time:?})");
+            }
+            Check::Fail { expected, found } => {
+                failed += 1;
+                println!("{name}: FAIL ({time:?})");
+                for line in answers::diff(&expected, &found) {
+                    println!("\t{line}");
+                }
+            }
+            Check::Missing => {
+                missing += 1;
+                println!("{name}: missing, no answer registered");
+            }
+            Check::Error(e) => {
+                failed += 1;
+                println!("{name}: FAIL while {e}");
+            }
+        }
+    }
+
+    println!("\n{passed} passed, {failed} failed, {missing} missing");
+    if failed > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+fn bench(options: BenchOptions) -> ExitCode {
+    // Read before running, the new report may overwrite it
+    let baseline = match &options.baseline {
+        Some(path) => match Report::load(path) {
+            Ok(report) => Some(report),
+            Err(e) => {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Report::load(&options.output).ok(),
+    };
+
+    let _quiet = parallel::Quiet::new();
+    let mut inputs = Inputs::new(None);
+    let mut report = Report { iterations: options.iterations, timings: Vec::new() };
+    let mut failed = false;
+
+    println!("{:<16} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+             "", "gen min", "gen median", "gen p95", "run min", "run median", "run p95");
+
+    // Like verify, the parts without an answer aren't run, some of them are
+    // still `todo!()`. Without answers every part is run.
+    let answers = Answers::load(answers::ANSWERS_PATH).ok();
+
+    for solution in select(options.day, options.part) {
+        let name = format!("Day {} - Part {}", solution.day, solution.part);
+        if answers.as_ref().is_some_and(|a| a.get(solution.day, solution.part).is_none()) {
+            println!("{name:<16} skipped, no answer registered");
+            continue;
+        }
+        let result = inputs
+            .get(solution.day)
+            .and_then(|text| bench::bench(&solution, text, options.iterations));
+
+        match result {
+            Ok(timing) => {
+                let (generator, runner) = (timing.generator, timing.runner);
+                println!("{name:<16} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+                         format!("{:.2?}", generator.min),
+                         format!("{:.2?}", generator.median),
+                         format!("{:.2?}", generator.p95),
+                         format!("{:.2?}", runner.min),
+                         format!("{:.2?}", runner.median),
+                         format!("{:.2?}", runner.p95));
+                report.timings.push(timing);
+            }
+            Err(e) => {
+                eprintln!("{name}: FAILED while {e}");
+                failed = true;
+            }
+        }
+    }
+
+    if let Err(e) = report.save(&options.output) {
+        eprintln!("{e}");
+        return ExitCode::FAILURE;
+    }
+
+    if let Some(baseline) = baseline {
+        let regressions = bench::compare(&baseline, &report, options.threshold, BENCH_FLOOR);
+        println!("\n{} regressions over {}%", regressions.len(), options.threshold);
+        for r in &regressions {
+            println!("\tDay {} - Part {} {}: {:.2?} -> {:.2?}", r.day, r.part, r.phase, r.before, r.after);
+        }
+        failed |= !regressions.is_empty();
+    }
+
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+fn play(day: u32, scale: (usize, usize), save: Option<String>) -> ExitCode {
+    let text = match solutions::read_input(&solutions::input_path(day)) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match day15::play_input(&text, scale, save.as_deref().map(Path::new)) {
+        Ok(score) => {
+            println!("GPS {score}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn debug(day: u32, input: Option<String>) -> ExitCode {
+    let text = match solutions::read_input(&input.unwrap_or_else(|| solutions::input_path(day))) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match day17::debug_input(&text) {
+        Ok(output) => {
+            println!("Output: {output}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn record(day: u32, input: Option<String>, format: Format, output: Option<String>) -> ExitCode {
+    let text = match solutions::read_input(&input.unwrap_or_else(|| solutions::input_path(day))) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut recorder = Recorder::new(RECORD_CELL);
+    let recorded = match day {
+        6 => day6::record_input(&text, &mut recorder),
+        14 => day14::record_input(&text, &mut recorder),
+        _ => day15::record_input(&text, &mut recorder),
+    };
+    let written = recorded.and_then(|()| {
+        let output = match format {
+            Format::Gif => {
+                let path = output.unwrap_or_else(|| format!("day{day}.gif"));
+                recorder.write_gif(&path, RECORD_DELAY)?;
+                path
+            }
+            Format::Png => {
+                let dir = output.unwrap_or_else(|| format!("day{day}-frames"));
+                recorder.write_pngs(&dir, &format!("day{day}"))?;
+                dir
+            }
+        };
+        Ok(output)
+    });
+
+    match written {
+        Ok(output) => {
+            println!("{} frames written to {output}", recorder.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+pub fn main(args: impl IntoIterator<Item = String>) -> ExitCode {
+    match parse_args(args) {
+        Ok(Command::Run { day: None, part, input: None, jobs: Some(jobs) }) => run_parallel(part, jobs),
+        Ok(Command::Run { day, part, input, .. }) => run(day, part, input),
+        Ok(Command::Verify { day, part, answers }) => verify(day, part, answers),
+        Ok(Command::Bench(options)) => bench(options),
+        Ok(Command::Play { day, scale, save }) => play(day, scale, save),
+        Ok(Command::Debug { day, input }) => debug(day, input),
+        Ok(Command::Record { day, input, format, output }) => record(day, input, format, output),
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+
+#[cfg(test)]
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn test_parse_run() {
+    assert_eq!(
+        parse_args(args("run --day 16 --part 2 --input path/to/file")),
+        Ok(Command::Run { day: Some(16), part: Some(2), input: Some(String::from("path/to/file")), jobs: None })
+    );
+    assert_eq!(
+        parse_args(args("run -d 3 -i -")),
+        Ok(Command::Run { day: Some(3), part: None, input: Some(String::from("-")), jobs: None })
+    );
+    assert_eq!(
+        parse_args(args("run --all --part 1")),
+        Ok(Command::Run { day: None, part: Some(1), input: None, jobs: None })
+    );
+    assert_eq!(
+        parse_args(args("run --all -j 8")),
+        Ok(Command::Run { day: None, part: None, input: None, jobs: Some(8) })
+    );
+    assert_eq!(parse_args(args("")), Ok(Command::Run { day: None, part: None, input: None, jobs: None }));
+    assert_eq!(parse_args(args("--help")), Ok(Command::Help));
+}
+
+#[test]
+fn test_parse_verify() {
+    assert_eq!(
+        parse_args(args("verify")),
+        Ok(Command::Verify { day: None, part: None, answers: None })
+    );
+    assert_eq!(
+        parse_args(args("verify --day 24 --answers other.toml")),
+        Ok(Command::Verify { day: Some(24), part: None, answers: Some(String::from("other.toml")) })
+    );
+    assert_eq!(parse_args(args("verify --input x")), Err(String::from("unknown argument `--input`")));
+}
+
+#[test]
+fn test_parse_bench() {
+    assert_eq!(parse_args(args("bench")), Ok(Command::Bench(BenchOptions::default())));
+    assert_eq!(
+        parse_args(args("bench -d 6 -n 50 --output new.json --baseline old.json --threshold 25")),
+        Ok(Command::Bench(BenchOptions {
+            day: Some(6),
+            iterations: 50,
+            output: String::from("new.json"),
+            baseline: Some(String::from("old.json")),
+            threshold: 25,
+            ..BenchOptions::default()
+        }))
+    );
+    assert_eq!(
+        parse_args(args("bench --iterations 0")),
+        Err(String::from("invalid value for --iterations: `0`, expected 1 to 100000"))
+    );
+}
+
+#[test]
+fn test_parse_play() {
+    assert_eq!(parse_args(args("play --day 15")), Ok(Command::Play { day: 15, scale: (1, 1), save: None }));
+    assert_eq!(
+        parse_args(args("play -d 15 --double --save moves.txt")),
+        Ok(Command::Play { day: 15, scale: (1, 2), save: Some(String::from("moves.txt")) })
+    );
+    assert_eq!(parse_args(args("play -d 15 --scale 2x3")), Ok(Command::Play { day: 15, scale: (2, 3), save: None }));
+    assert_eq!(
+        parse_args(args("play -d 15 --scale 2")),
+        Err(String::from("invalid value for --scale: `2`, expected <h>x<w> with 1 to 10"))
+    );
+    assert!(parse_args(args("play -d 15 --scale 0x2")).is_err());
+    assert_eq!(parse_args(args("play --double")), Err(String::from("play needs --day")));
+    assert_eq!(parse_args(args("play --day 6")), Err(String::from("day 6 can't be played, only day 15")));
+}
+
+#[test]
+fn test_parse_debug() {
+    assert_eq!(parse_args(args("debug --day 17")), Ok(Command::Debug { day: 17, input: None }));
+    assert_eq!(
+        parse_args(args("debug -d 17 -i small.txt")),
+        Ok(Command::Debug { day: 17, input: Some(String::from("small.txt")) })
+    );
+    assert_eq!(parse_args(args("debug")), Err(String::from("debug needs --day")));
+    assert_eq!(parse_args(args("debug --day 3")), Err(String::from("day 3 can't be debugged, only day 17")));
+    assert_eq!(parse_args(args("debug -d 17 -i -")), Err(String::from("debug can't read the input from stdin")));
+}
+
+#[test]
+fn test_parse_record() {
+    assert_eq!(
+        parse_args(args("record --day 14")),
+        Ok(Command::Record { day: 14, input: None, format: Format::Gif, output: None })
+    );
+    assert_eq!(
+        parse_args(args("record -d 6 -i small.txt --format png -o frames")),
+        Ok(Command::Record {
+            day: 6,
+            input: Some(String::from("small.txt")),
+            format: Format::Png,
+            output: Some(String::from("frames")),
+        })
+    );
+    assert_eq!(parse_args(args("record")), Err(String::from("record needs --day")));
+    assert_eq!(parse_args(args("record --day 7")), Err(String::from("day 7 can't be recorded, only days 6, 14 and 15")));
+    assert_eq!(
+        parse_args(args("record --day 6 --format mp4")),
+        Err(String::from("invalid value for --format: `mp4`, expected gif or png"))
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse_args(args("run")), Err(String::from("run needs --day or --all")));
+    assert_eq!(parse_args(args("run --day 2 --all")), Err(String::from("--day and --all can't be used together")));
+    assert_eq!(parse_args(args("run --all --input x")), Err(String::from("--input needs --day")));
+    assert_eq!(parse_args(args("run --day 3 --jobs 4")), Err(String::from("--jobs needs --all")));
+    assert_eq!(parse_args(args("run --day 26")), Err(String::from("invalid value for --day: `26`, expected 1 to 25")));
+    assert_eq!(parse_args(args("run --day 1 --part")), Err(String::from("missing value for --part")));
+    assert_eq!(parse_args(args("run --verbose")), Err(String::from("unknown argument `--verbose`")));
+    assert_eq!(parse_args(args("walk")), Err(String::from("unknown command `walk`")));
+}
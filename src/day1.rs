@@ -1,27 +1,26 @@
 #![allow(unused_variables, unused_mut)]
 
 use std::collections::HashMap;
+use crate::parse::{self, ParseError};
 
 type Input = (Vec<u32>, Vec<u32>);
 
 #[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Input {
+pub fn input_generator(input: &str) -> Result<Input, ParseError> {
     let mut v1 = Vec::new();
     let mut v2 = Vec::new();
-    
-    input
-        .lines()
-        .for_each(|l| {
-            let mut iter = l.trim().split_whitespace();
-    
-            let a: u32 = iter.next().unwrap().parse().unwrap();
-            let b: u32 = iter.next().unwrap().parse().unwrap();
-    
-            v1.push(a);
-            v2.push(b);
-        });
 
-    (v1, v2)
+    for line in parse::lines(1, input) {
+        let mut iter = line.trim().split_whitespace();
+
+        let a = iter.next().ok_or_else(|| line.error_end("a number"))?;
+        let b = iter.next().ok_or_else(|| line.error_end("a number"))?;
+
+        v1.push(line.parse(a, "a number")?);
+        v2.push(line.parse(b, "a number")?);
+    }
+
+    Ok((v1, v2))
 }
 
 #[aoc(day1, part1)]
@@ -81,9 +80,18 @@ mod tests {
 
     #[test]
     fn generator() {
-        let i = input_generator("100   20\n10  200\n20    5");
+        let i = input_generator("100   20\n10  200\n20    5").unwrap();
         assert_eq!(i, (vec![100, 10, 20], vec![20, 200, 5]));
     }
+
+    #[test]
+    fn generator_errors() {
+        let err = input_generator("100   20\n10  2x0").unwrap_err();
+        assert_eq!(err, ParseError::new(1, 2, 5, "a number", "2x0"));
+
+        let err = input_generator("100   20\n10").unwrap_err();
+        assert_eq!(err.to_string(), "day 1 line 2 column 3: expected a number, found end of line");
+    }
     
     #[test]
     fn p1_case1() {
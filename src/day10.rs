@@ -1,39 +1,25 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons)]
 
 use std::collections::HashSet;
-use crate::utils::get_m;
+use crate::utils::Grid;
+use crate::parse::ParseError;
 
-type Input = Vec<Vec<u32>>;
+type Input = Grid<u32>;
 
 #[aoc_generator(day10)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|line| {
-            line.trim()
-                .chars()
-                .map(|c| c.to_digit(10).unwrap_or(99))
-                .collect()
-        })
-        .collect()
+fn parse(input: &str) -> Result<Input, ParseError> {
+    Grid::try_parse(10, input, "a height digit or `.`", |c| match c {
+        '.' => Some(99),
+        _ => c.to_digit(10),
+    })
 }
 
 fn next_cells(input: &Input, i: usize, j: usize) -> Vec<(usize, usize)> {
     let target_val = input[i][j] + 1;
-    let i = i as i32;
-    let j = j as i32;
-
-    vec![(i - 1, j),
-         (i,     j + 1),
-         (i + 1, j),
-         (i,     j - 1)]
-        .iter()
-        .map(|(i, j)| {
-            let v: u32 = *get_m(input, *i, *j, &99);
-            (i, j, v)
-        })
-        .filter(|(_, _, v)| *v == target_val)
-        .map(|(i, j, _)| (*i as usize, *j as usize))
+
+    input
+        .neighbours4((i, j))
+        .filter(|(i, j)| input[*i][*j] == target_val)
         .collect()
 }
 
@@ -68,8 +54,8 @@ fn rate_trailhead(input: &Input, i: usize, j: usize) -> usize {
 #[aoc(day10, part1)]
 fn part1(input: &Input) -> i32 {
     let mut acc = 0;
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
+    for i in 0 .. input.height() {
+        for j in 0 .. input.width() {
             if input[i][j] == 0 {
                 acc += score_trailhead(input, i, j) as i32
             }
@@ -81,8 +67,8 @@ fn part1(input: &Input) -> i32 {
 #[aoc(day10, part2)]
 fn part2(input: &Input) -> i32 {
     let mut acc = 0;
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
+    for i in 0 .. input.height() {
+        for j in 0 .. input.width() {
             if input[i][j] == 0 {
                 acc += rate_trailhead(input, i, j) as i32
             }
@@ -102,14 +88,14 @@ mod tests {
                      7.12
                      8743
                      9654";
-        let result = parse(input);
+        let result = parse(input).unwrap();
         assert_eq!(result,
-                   vec![
+                   Grid::from(vec![
                        vec![8, 9, 0, 1],
                        vec![7, 99, 1, 2],
                        vec![8, 7, 4, 3],
                        vec![9, 6, 5, 4],
-                   ]);
+                   ]));
     }
 
     #[test]
@@ -121,7 +107,7 @@ mod tests {
              6543456
              7.....7
              8.....8
-             9.....9");
+             9.....9").unwrap();
 
         assert_eq!(score_trailhead(&input, 0, 3), 2);
 
@@ -132,7 +118,7 @@ mod tests {
              6543456
              765.987
              876....
-             987....");
+             987....").unwrap();
 
         assert_eq!(score_trailhead(&input, 0, 3), 4);
 
@@ -143,7 +129,7 @@ mod tests {
              4567654
              ...8..3
              ...9..2
-             .....01");
+             .....01").unwrap();
 
         assert_eq!(score_trailhead(&input, 0, 1), 1);
         assert_eq!(score_trailhead(&input, 6, 6), 2);
@@ -159,7 +145,7 @@ mod tests {
              45678903
              32019012
              01329801
-             10456732");
+             10456732").unwrap();
 
         assert_eq!(part1(&input), 36);
     }
@@ -173,7 +159,7 @@ mod tests {
              ..6543.
              ..7..4.
              ..8765.
-             ..9....");
+             ..9....").unwrap();
 
         assert_eq!(rate_trailhead(&input, 0, 5), 3);
 
@@ -184,7 +170,7 @@ mod tests {
              6543456
              765.987
              876....
-             987....");
+             987....").unwrap();
 
         assert_eq!(rate_trailhead(&input, 0, 3), 13);
 
@@ -194,7 +180,7 @@ mod tests {
              234567
              345678
              4.6789
-             56789.");
+             56789.").unwrap();
 
         assert_eq!(rate_trailhead(&input, 0, 0), 227);
     }
@@ -209,7 +195,7 @@ mod tests {
              45678903
              32019012
              01329801
-             10456732");
+             10456732").unwrap();
 
         assert_eq!(part2(&input), 81);
     }
@@ -2,11 +2,19 @@
 
 use memoize::memoize;
 
+use crate::parse::{self, ParseError};
+
 type Input = Vec<u64>;
 
 #[aoc_generator(day11)]
-fn parse(input: &str) -> Input {
-    input.trim().split_whitespace().map(|s| s.parse().unwrap()).collect()
+fn parse(input: &str) -> Result<Input, ParseError> {
+    parse::lines(11, input)
+        .flat_map(|line| {
+            line.trim()
+                .split_whitespace()
+                .map(move |s| line.parse(s, "a stone number"))
+        })
+        .collect()
 }
 
 fn even_digits(elem: u64) -> bool {
@@ -82,7 +90,7 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let input = parse("0 1 10 99 999");
+        let input = parse("0 1 10 99 999").unwrap();
         assert_eq!(input, vec![0, 1, 10, 99, 999]);
     }
 
@@ -117,7 +125,7 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let input = parse("125 17");
+        let input = parse("125 17").unwrap();
         assert_eq!(part1(&input), 55312);
     }
 }
@@ -1,16 +1,14 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
-type Input = Vec<Vec<char>>;
+type Input = Grid<char>;
 
-use crate::utils::get2d;
+use crate::utils::Grid;
+use crate::parse::ParseError;
 use std::collections::HashSet;
     
 #[aoc_generator(day12)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| l.trim().chars().collect())
-        .collect()
+fn parse(input: &str) -> Result<Input, ParseError> {
+    Grid::try_parse(12, input, "a plant", Some)
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -19,25 +17,17 @@ struct Block {
     elements: HashSet<(usize, usize)>,
 }
 
-fn neighbours(input: &Input, id: char, (i, j): (usize, usize)) -> Vec<(usize, usize)>{
-    let i = i as i32;
-    let j = j as i32;
-
-    [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)]
-        .iter()
-        .filter(|(i, j)| *get2d(input, *i, *j) == id)
-        .map(|(i,j)| (*i as usize, *j as usize))
+fn neighbours(input: &Input, id: char, pos: (usize, usize)) -> Vec<(usize, usize)>{
+    input
+        .neighbours4(pos)
+        .filter(|(i, j)| input[*i][*j] == id)
         .collect()
 }
 
-fn num_neighbours(input: &Input, id: char, (i, j): (usize, usize)) -> u32 {
-    let i = i as i32;
-    let j = j as i32;
-
-    [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)]
-        .iter()
-        .filter(|(i, j)| *get2d(input, *i, *j) == id)
-        .map(|(i,j)| (*i as usize, *j as usize))
+fn num_neighbours(input: &Input, id: char, pos: (usize, usize)) -> u32 {
+    input
+        .neighbours4(pos)
+        .filter(|(i, j)| input[*i][*j] == id)
         .count() as u32
 }
 
@@ -79,8 +69,8 @@ fn find_blocks(input: &Input) -> Vec<Block>{
     let mut visited = HashSet::<(usize, usize)>::new();
     let mut result = Vec::new();
 
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
+    for i in 0 .. input.height() {
+        for j in 0 .. input.width() {
             if !visited.contains(&(i, j)) {
 
                 let block = resolve_block(input, i, j);
@@ -118,7 +108,7 @@ fn num_sides(input: &Input, block: &Block) -> u32 {
         let j = *j as i32;
 
         // LEFT SIDE
-        if *get2d(input, i, j - 1) != id {
+        if *input.get_or(i, j - 1, &' ') != id {
             all_sides.insert((Left, i, j));
 
             if !all_sides.contains(&(Left, i + 1, j)) && !all_sides.contains(&(Left, i - 1, j)) {
@@ -126,7 +116,7 @@ fn num_sides(input: &Input, block: &Block) -> u32 {
             }
         }
         // RIGHT SIDE
-        if *get2d(input, i, j + 1) != id {
+        if *input.get_or(i, j + 1, &' ') != id {
             all_sides.insert((Right, i, j));
 
             if !all_sides.contains(&(Right, i + 1, j)) && !all_sides.contains(&(Right, i - 1, j)) {
@@ -134,7 +124,7 @@ fn num_sides(input: &Input, block: &Block) -> u32 {
             }
         }
         // TOP SIDE
-        if *get2d(input, i - 1, j) != id {
+        if *input.get_or(i - 1, j, &' ') != id {
             all_sides.insert((Top, i, j));
 
             if !all_sides.contains(&(Top, i, j + 1)) && !all_sides.contains(&(Top, i, j - 1)) {
@@ -142,7 +132,7 @@ fn num_sides(input: &Input, block: &Block) -> u32 {
             }
         }
         // BOTTOM SIDE
-        if *get2d(input, i + 1, j) != id {
+        if *input.get_or(i + 1, j, &' ') != id {
             all_sides.insert((Bottom, i, j));
 
             if !all_sides.contains(&(Bottom, i, j + 1)) && !all_sides.contains(&(Bottom, i, j -1)) {
@@ -187,15 +177,15 @@ mod tests {
             parse("AAAA
                    BBCD
                    BBCC
-                   EEEC");
+                   EEEC").unwrap();
         assert_eq!(
             input,
-            vec![
+            Grid::from(vec![
                 vec!['A', 'A', 'A', 'A'],
                 vec!['B', 'B', 'C', 'D'],
                 vec!['B', 'B', 'C', 'C'],
                 vec!['E', 'E', 'E', 'C'],
-            ]
+            ])
         );
     }
 
@@ -205,7 +195,7 @@ mod tests {
             parse("AAAAXXXX
                    BBCD---X
                    BBCCXXXX
-                   EEEC-X-X");
+                   EEEC-X-X").unwrap();
 
         assert_eq!(
             resolve_block(&input, 0, 0),
@@ -238,7 +228,7 @@ mod tests {
             parse("AAAA
                    BBCD
                    BBCC
-                   EEEC");
+                   EEEC").unwrap();
 
         let blocks = find_blocks(&input);
         assert_eq!(blocks.len(), 5);
@@ -266,7 +256,7 @@ mod tests {
                    OXOXO
                    OOOOO
                    OXOXO
-                   OOOOO");
+                   OOOOO").unwrap();
 
         let blocks = find_blocks(&input);
         assert_eq!(blocks.len(), 5);
@@ -293,7 +283,7 @@ mod tests {
             parse("AAAA
                    BBCD
                    BBCC
-                   EEEC");
+                   EEEC").unwrap();
 
         let blocks = find_blocks(&input);
         assert_eq!(blocks.len(), 5);
@@ -326,7 +316,7 @@ mod tests {
                    VVIIICJJEE
                    MIIIIIJJEE
                    MIIISIJEEE
-                   MMMISSJEEE");
+                   MMMISSJEEE").unwrap();
         assert_eq!(part1(&input), 1930);
     }
 
@@ -336,7 +326,7 @@ mod tests {
             parse("AAAA
                    BBCD
                    BBCC
-                   EEEC");
+                   EEEC").unwrap();
 
         let blocks = find_blocks(&input);
         assert_eq!(blocks.len(), 5);
@@ -364,7 +354,7 @@ mod tests {
                    EXXXX
                    EEEEE
                    EXXXX
-                   EEEEE");
+                   EEEEE").unwrap();
 
         let blocks = find_blocks(&input);
         assert_eq!(blocks.len(), 3);
@@ -387,7 +377,7 @@ mod tests {
                    AAABBA
                    ABBAAA
                    ABBAAA
-                   AAAAAA");
+                   AAAAAA").unwrap();
 
         let blocks = find_blocks(&input);
         assert_eq!(blocks.len(), 3);
@@ -414,7 +404,7 @@ mod tests {
                    VVIIICJJEE
                    MIIIIIJJEE
                    MIIISIJEEE
-                   MMMISSJEEE");
+                   MMMISSJEEE").unwrap();
         assert_eq!(part2(&input), 1206);
     }
 }
@@ -3,6 +3,7 @@
 use regex::Regex;
 use memoize::memoize;
 use nalgebra::*;
+use crate::parse::{self, Line, ParseError};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 struct Entry {
@@ -16,32 +17,37 @@ struct Entry {
 
 type Input = Vec<Entry>;
 
+// Returns the two numbers of the line matching `re`
+fn capture_pair(line: &Line, re: &Regex, expected: &str) -> Result<(u32, u32), ParseError> {
+    let cc = re.captures(line.text).ok_or_else(|| line.error(line.trim(), expected))?;
+    let a = line.parse(cc.get(1).unwrap().as_str(), "a number")?;
+    let b = line.parse(cc.get(2).unwrap().as_str(), "a number")?;
+    Ok((a, b))
+}
+
 #[aoc_generator(day13)]
-fn parse(input: &str) -> Input {
-    let lines: Vec<&str> = input
-        .lines()
-        .map(|l| l.trim())
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let button_a_re = Regex::new(r"^\s*Button A: X\+(\d+), Y\+(\d+)\s*$").unwrap();
+    let button_b_re = Regex::new(r"^\s*Button B: X\+(\d+), Y\+(\d+)\s*$").unwrap();
+    let price_re = Regex::new(r"^\s*Prize: X\=(\d+), Y\=(\d+)\s*$").unwrap();
+
+    let lines: Vec<Line> = parse::lines(13, input)
         .filter(|l| !l.is_empty())
         .collect();
 
     lines
         .chunks(3)
         .map(|lines| {
-            let button_re = Regex::new(r"X\+(\d+), Y\+(\d+)").unwrap();
-            let price_re = Regex::new(r"X\=(\d+), Y\=(\d+)").unwrap();
-
-            let ac = button_re.captures(lines[0]).unwrap();
-            let bc = button_re.captures(lines[1]).unwrap();
-            let pr = price_re.captures(lines[2]).unwrap();
-            
-            Entry {
-                ax: ac[1].parse::<u32>().unwrap(),
-                ay: ac[2].parse::<u32>().unwrap(),
-                bx: bc[1].parse::<u32>().unwrap(),
-                by: bc[2].parse::<u32>().unwrap(),
-                price_x: pr[1].parse::<u32>().unwrap(),
-                price_y: pr[2].parse::<u32>().unwrap(),
-            }})
+            if lines.len() < 3 {
+                return Err(ParseError::eof(13, input, "`Prize: X=<n>, Y=<n>`"));
+            }
+
+            let (ax, ay) = capture_pair(&lines[0], &button_a_re, "`Button A: X+<n>, Y+<n>`")?;
+            let (bx, by) = capture_pair(&lines[1], &button_b_re, "`Button B: X+<n>, Y+<n>`")?;
+            let (price_x, price_y) = capture_pair(&lines[2], &price_re, "`Prize: X=<n>, Y=<n>`")?;
+
+            Ok(Entry { ax, ay, bx, by, price_x, price_y })
+        })
         .collect()
 }
 
@@ -149,7 +155,7 @@ mod tests {
              
              Button A: X+69, Y+23
              Button B: X+27, Y+71
-             Prize: X=18641, Y=10279");
+             Prize: X=18641, Y=10279").unwrap();
 
         assert_eq!(input, vec![
             Entry { ax: 94, ay: 34, bx: 22, by: 67, price_x: 8400, price_y: 5400 },
@@ -200,7 +206,7 @@ mod tests {
              
              Button A: X+69, Y+23
              Button B: X+27, Y+71
-             Prize: X=18641, Y=10279");
+             Prize: X=18641, Y=10279").unwrap();
         
         assert_eq!(part1(&input), 480);
     }
@@ -222,7 +228,7 @@ mod tests {
              
              Button A: X+69, Y+23
              Button B: X+27, Y+71
-             Prize: X=18641, Y=10279");
+             Prize: X=18641, Y=10279").unwrap();
         
         assert_eq!(part2(&input), 875318608908);
     }
@@ -2,7 +2,11 @@
 
 use regex::Regex;
 use std::collections::HashMap;
-use std::{thread, time};
+use std::error::Error;
+use std::{io, thread, time};
+use crate::export::{self, Recorder};
+use crate::parse::{self, ParseError};
+use crate::viz::{self, Frame, Style};
 
 
 #[derive(Debug, PartialEq, Clone)]
@@ -13,20 +17,37 @@ struct Entry {
 
 type Input = Vec<Entry>;
 
-#[aoc_generator(day14)]
-fn parse(input: &str) -> Input {
-    let entry_re = Regex::new(r"p\=(\d+),(\d+) v\=(-?\d+),(-?\d+)").unwrap();
+// Size of the room, seconds to simulate for the safety factor and seconds
+// to search for the easter egg.
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    width: u32,
+    height: u32,
+    steps: u32,
+    search_steps: u32,
+}
 
-    input
-        .lines()
-        .map(|l| {
-            let cc = entry_re.captures(l).unwrap();
-            let px = cc[1].parse::<u32>().unwrap();
-            let py = cc[2].parse::<u32>().unwrap();
-            let vx = cc[3].parse::<i32>().unwrap();
-            let vy = cc[4].parse::<i32>().unwrap();
+impl Default for Config {
+    fn default() -> Self {
+        Self { width: 101, height: 103, steps: 100, search_steps: 50000 }
+    }
+}
+
+#[aoc_generator(day14)]
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let entry_re = Regex::new(r"^\s*p\=(\d+),(\d+) v\=(-?\d+),(-?\d+)\s*$").unwrap();
+
+    parse::lines(14, input)
+        .map(|line| {
+            let cc = entry_re
+                .captures(line.text)
+                .ok_or_else(|| line.error(line.trim(), "`p=<x>,<y> v=<dx>,<dy>`"))?;
+            let px = line.parse(cc.get(1).unwrap().as_str(), "a position")?;
+            let py = line.parse(cc.get(2).unwrap().as_str(), "a position")?;
+            let vx = line.parse(cc.get(3).unwrap().as_str(), "a velocity")?;
+            let vy = line.parse(cc.get(4).unwrap().as_str(), "a velocity")?;
             
-            Entry { pos: (px,py), vel: (vx,vy) }
+            Ok(Entry { pos: (px,py), vel: (vx,vy) })
         })
         .collect()
 }
@@ -45,24 +66,61 @@ fn state_map(input: &Input) -> HashMap::<&(u32, u32), Vec<&Entry>> {
     locations
 }
 
-fn print_state(input: &Input, width: u32, height: u32) {
-    let locations = state_map(input);
-
-    for y in 0 .. height {
-        for x in 0 .. width {
-            if let Some(entries) = locations.get(&(x, y)) {
-                // print!("{}", entries.len());
-                print!("\u{25A0}")
-            //} else if x == width / 2 || y == height / 2 {
-            //    print!(" ");
-            } else {
-                print!(" ");
-            }
+// The room with a square wherever there are robots
+struct State<'a> {
+    locations: HashMap<&'a (u32, u32), Vec<&'a Entry>>,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> State<'a> {
+    fn new(input: &'a Input, width: u32, height: u32) -> Self {
+        Self { locations: state_map(input), width, height }
+    }
+}
+
+impl Frame for State<'_> {
+    fn height(&self) -> usize {
+        self.height as usize
+    }
+
+    fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    fn cell(&self, i: usize, j: usize) -> (char, Style) {
+        if self.locations.contains_key(&(j as u32, i as u32)) {
+            ('\u{25A0}', Style::Object)
+        } else {
+            (' ', Style::Plain)
         }
-        println!();
     }
 }
 
+fn print_state(input: &Input, width: u32, height: u32) {
+    viz::print(&State::new(input, width, height));
+}
+
+// Records the room before the first step and after each of the `steps`
+fn record(input: &Input, config: &Config, steps: u32, recorder: &mut Recorder) -> io::Result<()> {
+    let Config { width, height, .. } = *config;
+    let mut input = input.clone();
+
+    recorder.add_frame(&State::new(&input, width, height), export::style_color)?;
+    for _ in 0 .. steps {
+        sim_step(&mut input, width, height);
+        recorder.add_frame(&State::new(&input, width, height), export::style_color)?;
+    }
+    Ok(())
+}
+
+// Records the first seconds of a puzzle input, run by `aoc-2024 record`
+pub(crate) fn record_input(text: &str, recorder: &mut Recorder) -> Result<(), Box<dyn Error>> {
+    let config = Config::default();
+    record(&parse(text)?, &config, config.steps, recorder)?;
+    Ok(())
+}
+
 fn sim_entry(entry: &mut Entry, width: u32, height: u32) {
     let mut x = entry.pos.0 as i32 + entry.vel.0;
     let mut y = entry.pos.1 as i32 + entry.vel.1;
@@ -139,12 +197,15 @@ fn safety_factor(input: &Input, width: u32, height: u32) -> u32 {
 
 #[aoc(day14, part1)]
 fn part1(input: &Input) -> u32 {
-    let width: u32 = 101;
-    let height: u32  = 103;
-    
+    part1_with(input, &Config::default())
+}
+
+fn part1_with(input: &Input, config: &Config) -> u32 {
+    let Config { width, height, .. } = *config;
+
     let mut state: Input = input.clone();
     //print_state(&state, width, height);
-    simulate(&mut state, width, height, 100);
+    simulate(&mut state, width, height, config.steps);
     //print_state(&input, width, height);
 
     safety_factor(&state, width, height)
@@ -152,14 +213,17 @@ fn part1(input: &Input) -> u32 {
 
 #[aoc(day14, part2)]
 fn part2(input: &Input) -> u32 {
-    let width: u32 = 101;
-    let height: u32  = 103;
+    part2_with(input, &Config::default())
+}
+
+fn part2_with(input: &Input, config: &Config) -> u32 {
+    let Config { width, height, .. } = *config;
     let mut state: Input = input.clone();
 
     // We can use the safety_factor as an heuristic to search for
     // the drawing.
     let mut sf = Vec::<(u32, u32)>::new();
-    for step in 1 ..= 50000 {
+    for step in 1 ..= config.search_steps {
         sim_step(&mut state, width, height);
         let safety_factor = safety_factor(&state, width, height);
         sf.push((safety_factor, step));
@@ -209,6 +273,8 @@ mod tests {
     const WIDTH: u32 = 11;
     const HEIGHT: u32 = 7;
 
+    const EXAMPLE: Config = Config { width: WIDTH, height: HEIGHT, steps: 100, search_steps: 100 };
+
     #[test]
     fn test_parse() {
         let input = parse(
@@ -224,7 +290,7 @@ mod tests {
              p=7,3 v=-1,2
              p=2,4 v=2,-3
              p=9,5 v=-3,-3"
-        );
+        ).unwrap();
         assert_eq!(input, vec![
             Entry { pos: (0, 4), vel: (3, -3) },
             Entry { pos: (6, 3), vel: (-1, -3) },
@@ -292,7 +358,7 @@ mod tests {
              p=7,3 v=-1,2
              p=2,4 v=2,-3
              p=9,5 v=-3,-3"
-        );
+        ).unwrap();
 
 
         print_state(&input, WIDTH, HEIGHT);
@@ -322,7 +388,7 @@ mod tests {
              p=7,3 v=-1,2
              p=2,4 v=2,-3
              p=9,5 v=-3,-3"
-        );
+        ).unwrap();
 
 
         print_state(&input, WIDTH, HEIGHT);
@@ -335,4 +401,32 @@ mod tests {
         assert_eq!(q4(&input, WIDTH, HEIGHT), 1);
         assert_eq!(safety_factor(&input, WIDTH, HEIGHT), 12);
     }
+
+    #[test]
+    fn test_part1_example() {
+        let input = parse(
+            "p=0,4 v=3,-3
+             p=6,3 v=-1,-3
+             p=10,3 v=-1,2
+             p=2,0 v=2,-1
+             p=0,0 v=1,3
+             p=3,0 v=-2,-2
+             p=7,6 v=-1,-3
+             p=3,0 v=-1,-2
+             p=9,3 v=2,3
+             p=7,3 v=-1,2
+             p=2,4 v=2,-3
+             p=9,5 v=-3,-3"
+        ).unwrap();
+
+        assert_eq!(part1_with(&input, &EXAMPLE), 12);
+    }
+
+    #[test]
+    fn test_record() {
+        let input = parse("p=2,4 v=2,-3").unwrap();
+        let mut recorder = Recorder::new(1);
+        record(&input, &EXAMPLE, 5, &mut recorder).unwrap();
+        assert_eq!(recorder.len(), 6);
+    }
 }
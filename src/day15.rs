@@ -1,6 +1,14 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
-use std::{thread, time};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+use crate::export::{self, Recorder};
+use crate::utils::{self, Rng};
+use crate::viz::{self, CellFn, GridFrame, Key, KeyMode, Player, Style};
+use crate::parse::{self, Line, ParseError};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Instruction {
@@ -19,95 +27,175 @@ enum GridElem {
     SmallBox,
     BoxLeft,
     BoxRight,
+    // A box of any size, all its cells have the same id
+    Object(u32),
     Robot
 }
 
 use GridElem::*;
 
-type Grid = Vec<Vec<GridElem>>;
+type Grid = utils::Grid<GridElem>;
 type Instructions = Vec<Instruction>;
 type Input = (Grid, Instructions);
 
-#[aoc_generator(day15, part1)]
-fn parse(input: &str) -> Input {
-    let mut lit = input.lines();
-    let mut grid = Vec::new();
-    
-    loop {
-        if let Some(line) = lit.next() {
-            let line = line.trim();
-            if line.is_empty() {
-                break;
-            }
+fn parse_instructions<'a>(lines: impl Iterator<Item = Line<'a>>) -> Result<Instructions, ParseError> {
+    let mut instructions = Vec::new();
+    for line in lines {
+        for (col, c) in line.chars() {
+            let instruction = match c {
+                '>' => MoveE,
+                '<' => MoveW,
+                '^' => MoveN,
+                'v' => MoveS,
+                _   => return Err(line.error_at(col, c, "one of `<>^v`")),
+            };
+            instructions.push(instruction);
+        }
+    }
+    Ok(instructions)
+}
 
-            let row = line.chars().map(|c| match c {
-                '#' => Wall,
-                'O' => SmallBox,
-                '[' => BoxLeft,
-                ']' => BoxRight,
-                '@' => Robot,
-                _   => Empty
-            }).collect();
+// Reads the map until the blank line, `cell` expands each character
+// into the grid elements it represents.
+fn parse_map<'a, F>(lit: &mut impl Iterator<Item = Line<'a>>, expected: &str, cell: F) -> Result<Grid, ParseError>
+where
+    F: Fn(char) -> Option<Vec<GridElem>>,
+{
+    let mut grid: Vec<Vec<GridElem>> = Vec::new();
 
-            grid.push(row);
-        } else {
+    for line in lit.by_ref() {
+        if line.is_empty() {
             break;
-        };
+        }
+
+        let mut row = Vec::new();
+        for (col, c) in line.chars() {
+            row.extend(cell(c).ok_or_else(|| line.error_at(col, c, expected))?);
+        }
+
+        if let Some(first) = grid.first() {
+            if row.len() != first.len() {
+                return Err(line.error(line.trim(), &format!("row of {} cells", first.len())));
+            }
+        }
+        grid.push(row);
     }
 
-    let mut instructions = Vec::new();
-    while let Some(line) = lit.next() {
-        let line = line.trim();
-        line.chars().map(|c| match c {
-            '>' => MoveE,
-            '<' => MoveW,
-            '^' => MoveN,
-            'v' => MoveS,
-            _   => panic!("unexpected input!"),
-        }).for_each(|i| instructions.push(i));
+    let grid = Grid::from(grid);
+    if grid.find(&Robot).is_none() {
+        return Err(ParseError::new(15, 1, 1, "a map with a robot `@`", "a map without `@`"));
     }
-    
-    (grid, instructions)
+    Ok(grid)
+}
+
+#[aoc_generator(day15, part1)]
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let mut lit = parse::lines(15, input);
+
+    let grid = parse_map(&mut lit, "one of `#.O[]@`", |c| match c {
+        '#' => Some(vec![Wall]),
+        'O' => Some(vec![SmallBox]),
+        '[' => Some(vec![BoxLeft]),
+        ']' => Some(vec![BoxRight]),
+        '@' => Some(vec![Robot]),
+        '.' => Some(vec![Empty]),
+        _   => None,
+    })?;
+
+    let instructions = parse_instructions(lit)?;
+    Ok((grid, instructions))
 }
 
 #[aoc_generator(day15, part2)]
-fn parse_double(input: &str) -> Input {
-    let mut lit = input.lines();
-    let mut grid = Vec::new();
-    
-    loop {
-        if let Some(line) = lit.next() {
-            let line = line.trim();
-            if line.is_empty() {
-                break;
+fn parse_double(input: &str) -> Result<Input, ParseError> {
+    let mut lit = parse::lines(15, input);
+
+    let grid = parse_map(&mut lit, "one of `#.O@`", |c| match c {
+        '#' => Some(vec![Wall, Wall]),
+        'O' => Some(vec![BoxLeft, BoxRight]),
+        '@' => Some(vec![Robot, Empty]),
+        '.' => Some(vec![Empty, Empty]),
+        _   => None,
+    })?;
+
+    let instructions = parse_instructions(lit)?;
+    Ok((grid, instructions))
+}
+
+// Makes every cell of a single width grid `height` x `width` cells. Boxes
+// become objects of that size, `SmallBox` and `[]` when they fit.
+fn scale(grid: &Grid, height: usize, width: usize) -> Grid {
+    let mut scaled = Grid::new(grid.width() * width, grid.height() * height, Empty);
+    let mut next_id = 0;
+
+    for ((i, j), elem) in grid.iter() {
+        let mut block = vec![*elem; height * width];
+        match (elem, height, width) {
+            (SmallBox, 1, 1) => {}
+            (SmallBox, 1, 2) => block = vec![BoxLeft, BoxRight],
+            (SmallBox, _, _) => {
+                block.fill(Object(next_id));
+                next_id += 1;
             }
+            (Robot, _, _) => {
+                block.fill(Empty);
+                block[0] = Robot;
+            }
+            _ => {}
+        }
 
-            let row = line.chars().flat_map(|c| match c {
-                '#' => vec![Wall, Wall],
-                'O' => vec![BoxLeft, BoxRight],
-                '@' => vec![Robot, Empty],
-                _   => vec![Empty, Empty]
-            }).collect();
+        for (k, elem) in block.into_iter().enumerate() {
+            scaled[(i * height + k / width, j * width + k % width)] = elem;
+        }
+    }
+    scaled
+}
 
-            grid.push(row);
-        } else {
-            break;
-        };
+fn format_instruction(instruction: &Instruction) -> char {
+    match instruction {
+        MoveN => '^',
+        MoveS => 'v',
+        MoveW => '<',
+        MoveE => '>',
     }
+}
 
-    let mut instructions = Vec::new();
-    while let Some(line) = lit.next() {
-        let line = line.trim();
-        line.chars().map(|c| match c {
-            '>' => MoveE,
-            '<' => MoveW,
-            '^' => MoveN,
-            'v' => MoveS,
-            _   => panic!("unexpected input!"),
-        }).for_each(|i| instructions.push(i));
+fn format_instructions(instructions: &[Instruction]) -> String {
+    instructions.iter().map(format_instruction).collect()
+}
+
+// Instructions per line when writing them, like the puzzle inputs
+const INSTRUCTIONS_WIDTH: usize = 1000;
+
+fn format_map(grid: &Grid) -> String {
+    let mut text = String::new();
+    for row in grid.rows() {
+        text.extend(row.iter().map(format_elem));
+        text.push('\n');
     }
-    
-    (grid, instructions)
+    text
+}
+
+// A puzzle input: the map, a blank line and the instructions wrapped in
+// lines of `INSTRUCTIONS_WIDTH`. The puzzle inputs have no objects, grids
+// with them are an error.
+fn to_text((grid, instructions): &Input) -> Result<String, String> {
+    if let Some((pos, _)) = grid.iter().find(|(_, elem)| matches!(elem, Object(_))) {
+        return Err(format!("the object at {pos:?} can't be written as puzzle input"));
+    }
+
+    let mut text = format_map(grid);
+    text.push('\n');
+    for line in instructions.chunks(INSTRUCTIONS_WIDTH) {
+        text.push_str(&format_instructions(line));
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+fn write_text<P: AsRef<Path>>(path: P, input: &Input) -> io::Result<()> {
+    let text = to_text(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    fs::write(path, text)
 }
 
 fn format_elem(elem: &GridElem) -> char{
@@ -116,208 +204,396 @@ fn format_elem(elem: &GridElem) -> char{
         SmallBox => 'O',
         BoxLeft => '[',
         BoxRight => ']',
+        // Only for showing them, neighbour objects get different letters
+        // most of the times
+        Object(id) => (b'a' + (id % 26) as u8) as char,
         Robot => '@',
         Empty => '.',
     }
 }
-fn print_grid(grid: &Grid) {
-    for i in 0 .. grid.len() {
-        for j in 0 .. grid[i].len() {
-            print!("{}", format_elem(&grid[i][j]));
-        }
-        println!()
+
+fn elem_cell(elem: &GridElem) -> (char, Style) {
+    let style = match elem {
+        Wall => Style::Wall,
+        SmallBox | BoxLeft | BoxRight | Object(_) => Style::Object,
+        Robot => Style::Agent,
+        Empty => Style::Plain,
+    };
+    (format_elem(elem), style)
+}
+
+fn grid_frame(grid: &Grid) -> GridFrame<'_, GridElem, CellFn<GridElem>> {
+    GridFrame::new(grid, |_, elem| elem_cell(elem))
+}
+
+// Records the grid before the first move and after each move
+fn record_moves((grid, instructions): &Input, recorder: &mut Recorder) -> io::Result<()> {
+    let mut grid = grid.clone();
+    let mut robot = find_robot(&grid).unwrap();
+
+    recorder.add_frame(&grid_frame(&grid), export::style_color)?;
+    for i in instructions {
+        robot = move_elem(&mut grid, robot, i);
+        recorder.add_frame(&grid_frame(&grid), export::style_color)?;
     }
+    Ok(())
+}
+
+// Records the moves of a puzzle input, run by `aoc-2024 record`
+pub(crate) fn record_input(text: &str, recorder: &mut Recorder) -> Result<(), Box<dyn Error>> {
+    record_moves(&parse(text)?, recorder)?;
+    Ok(())
+}
+
+fn print_grid(grid: &Grid) {
+    viz::print(&grid_frame(grid));
 }
 
 fn find_robot(grid: &Grid) -> Option<(usize, usize)> {
-    for i in 0 .. grid.len() {
-        for j in 0 .. grid[i].len() {
-            if grid[i][j] == Robot {
-                return Some((i, j));
-            }
-        }
-    }
-    None
+    grid.find(&Robot)
 }
 
-fn target_pos((i, j): (usize, usize), instruction: &Instruction) -> (usize, usize){
+fn direction(instruction: &Instruction) -> (i32, i32) {
     match instruction {
-        MoveE => (i, j + 1),
-        MoveN => (i - 1, j),
-        MoveS => (i + 1, j),
-        MoveW => (i, j - 1),
+        MoveE => (0, 1),
+        MoveN => (-1, 0),
+        MoveS => (1, 0),
+        MoveW => (0, -1),
     }
 }
 
-fn can_move_to(grid: &Grid, pos: (usize, usize), instruction: &Instruction) -> bool {
-    let (i, j) = pos;
+// The cell next to `pos`, which must be inside the grid
+fn target_pos((i, j): (usize, usize), instruction: &Instruction) -> (usize, usize){
+    let (di, dj) = direction(instruction);
+    ((i as i32 + di) as usize, (j as i32 + dj) as usize)
+}
+
+// Cells of the object covering `pos`, a robot or a box of any size
+fn object_cells(grid: &Grid, (i, j): (usize, usize)) -> Vec<(usize, usize)> {
     match grid[i][j] {
-        Wall => false,
-        Empty => true,
-        Robot | SmallBox => can_move_to(grid, target_pos(pos, instruction), instruction),
-        BoxLeft => {
-            if let MoveN | MoveS = instruction {
-                let tpos_left = target_pos(pos, instruction);
-                let tpos_right = target_pos((i, j+1), instruction);
-                can_move_to(grid, tpos_left, instruction) && can_move_to(grid, tpos_right, instruction)
-            } else {
-                can_move_to(grid, target_pos(pos, instruction), instruction)
+        Wall | Empty => vec![],
+        Robot | SmallBox => vec![(i, j)],
+        BoxLeft => vec![(i, j), (i, j + 1)],
+        BoxRight => vec![(i, j - 1), (i, j)],
+        Object(id) => {
+            let mut cells = vec![(i, j)];
+            let mut next = 0;
+            while let Some(&pos) = cells.get(next) {
+                next += 1;
+                for n in grid.neighbours4(pos) {
+                    if grid[n] == Object(id) && !cells.contains(&n) {
+                        cells.push(n);
+                    }
+                }
             }
-            
-        },
-        BoxRight => {
-            if let MoveN | MoveS = instruction {
-                let tpos_left = target_pos((i, j-1), instruction);
-                let tpos_right = target_pos(pos, instruction);
-                can_move_to(grid, tpos_left, instruction) && can_move_to(grid, tpos_right, instruction)
-            } else {
-                can_move_to(grid, target_pos(pos, instruction), instruction)
+            cells
+        }
+    }
+}
+
+// Cells moving when the object at `pos` follows `instruction`, its own
+// first, or None when something hits a wall
+fn pushed(grid: &Grid, pos: (usize, usize), instruction: &Instruction) -> Option<Vec<(usize, usize)>> {
+    let mut moved = object_cells(grid, pos);
+    let mut next = 0;
+
+    while let Some(&pos) = moved.get(next) {
+        next += 1;
+        // Outside the grid is a wall, the maps may have no border
+        let (di, dj) = direction(instruction);
+        if grid.get(pos.0 as i32 + di, pos.1 as i32 + dj).is_none_or(|elem| *elem == Wall) {
+            return None;
+        }
+        let target = target_pos(pos, instruction);
+        for cell in object_cells(grid, target) {
+            if !moved.contains(&cell) {
+                moved.push(cell);
             }
         }
     }
+    Some(moved)
 }
 
+// Moves the element at `pos` and everything it pushes, returns where the
+// element ends
 fn move_elem(grid: &mut Grid, pos: (usize, usize), instruction: &Instruction) -> (usize, usize) {
-    let (i, j) = pos;
-
-    match grid[i][j] {
+    match pushed(grid, pos, instruction) {
+        Some(moved) if !moved.is_empty() => {
+            let elems: Vec<_> = moved.into_iter().map(|p| (p, grid[p])).collect();
+            for (p, _) in &elems {
+                grid[*p] = Empty;
+            }
+            for (p, elem) in elems {
+                grid[target_pos(p, instruction)] = elem;
+            }
+            target_pos(pos, instruction)
+        }
         // Walls and empty space cannot move, return the same
-        Wall | Empty => (i, j),
+        _ => pos,
+    }
+}
 
-        BoxLeft => {
-            if let MoveN | MoveS = instruction {
-                let tpos_left = target_pos(pos, instruction);
-                let tpos_right = target_pos((i, j+1), instruction);
+// A move of the robot, with the cells it pushed and what they held before
+// moving. Blocked moves push nothing.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Delta {
+    instruction: Instruction,
+    moved: Vec<((usize, usize), GridElem)>,
+}
 
-                if can_move_to(grid, tpos_left, instruction) && can_move_to(grid, tpos_right, instruction) {
-                    move_elem(grid, tpos_left, instruction);
-                    move_elem(grid, tpos_right, instruction);
+// The grid and the robot after a list of moves, which can be undone and
+// redone to look at the warehouse at any step
+#[derive(Debug, Clone)]
+struct Warehouse {
+    grid: Grid,
+    robot: (usize, usize),
+    // The moves after `step` were undone
+    history: Vec<Delta>,
+    step: usize,
+}
 
-                    grid[tpos_left.0][tpos_left.1] = grid[i][j];
-                    grid[i][j] = Empty;
+impl Warehouse {
+    fn new(grid: &Grid) -> Option<Self> {
+        let robot = find_robot(grid)?;
+        Some(Self { grid: grid.clone(), robot, history: Vec::new(), step: 0 })
+    }
 
-                    grid[tpos_right.0][tpos_right.1] = grid[i][j+1];
-                    grid[i][j+1] = Empty;
-                    tpos_left
-                } else {
-                    pos
-                }
-            } else {
-                let tpos = target_pos(pos, instruction);
-
-                // Try to move the target position
-                move_elem(grid, tpos, instruction);
-                
-                // Check if target pos is empty
-                let (ti, tj) = tpos;
-                if grid[ti][tj] == Empty {
-                    grid[ti][tj] = grid[i][j];
-                    grid[i][j] = Empty;
-                    tpos
-                } else {
-                    pos
-                }
-            }
-            
-        },
+    // Runs all the instructions and stays at the end
+    fn run(grid: &Grid, instructions: &[Instruction]) -> Option<Self> {
+        let mut warehouse = Self::new(grid)?;
+        for i in instructions {
+            warehouse.apply(*i);
+        }
+        Some(warehouse)
+    }
 
-        BoxRight => {
-            if let MoveN | MoveS = instruction {
-                let tpos_left = target_pos((i, j-1), instruction);
-                let tpos_right = target_pos(pos, instruction);
+    fn grid(&self) -> &Grid {
+        &self.grid
+    }
 
-                if can_move_to(grid, tpos_left, instruction) && can_move_to(grid, tpos_right, instruction) {
-                    move_elem(grid, tpos_left, instruction);
-                    move_elem(grid, tpos_right, instruction);
+    fn robot(&self) -> (usize, usize) {
+        self.robot
+    }
 
-                    grid[tpos_left.0][tpos_left.1] = grid[i][j-1];
-                    grid[i][j-1] = Empty;
+    fn step(&self) -> usize {
+        self.step
+    }
 
-                    grid[tpos_right.0][tpos_right.1] = grid[i][j];
-                    grid[i][j] = Empty;
-                    tpos_right
-                } else {
-                    pos
-                }
-            } else {
-                let tpos = target_pos(pos, instruction);
-
-                // Try to move the target position
-                move_elem(grid, tpos, instruction);
-                
-                // Check if target pos is empty
-                let (ti, tj) = tpos;
-                if grid[ti][tj] == Empty {
-                    grid[ti][tj] = grid[i][j];
-                    grid[i][j] = Empty;
-                    tpos
+    // Moves recorded, including the undone ones
+    fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    // The moves up to the current step
+    fn instructions(&self) -> Instructions {
+        self.history[.. self.step].iter().map(|delta| delta.instruction).collect()
+    }
+
+    fn score(&self) -> u32 {
+        score(&self.grid)
+    }
+
+    // Moves the elements of `delta` forward, or back to where they were
+    fn shift(&mut self, delta: &Delta, forward: bool) {
+        let moves = delta.moved.iter().map(|(pos, elem)| (*pos, target_pos(*pos, &delta.instruction), *elem));
+        let moves: Vec<_> = if forward {
+            moves.collect()
+        } else {
+            moves.map(|(from, to, elem)| (to, from, elem)).collect()
+        };
+
+        for (from, _, _) in &moves {
+            self.grid[*from] = Empty;
+        }
+        for (_, to, elem) in &moves {
+            self.grid[*to] = *elem;
+        }
+        if let Some((_, to, _)) = moves.first() {
+            self.robot = *to;
+        }
+    }
+
+    // Applies a new move, dropping the undone ones. Returns if the robot moved.
+    fn apply(&mut self, instruction: Instruction) -> bool {
+        self.history.truncate(self.step);
+
+        let moved = pushed(&self.grid, self.robot, &instruction).unwrap_or_default();
+        let delta = Delta {
+            instruction,
+            moved: moved.into_iter().map(|p| (p, self.grid[p])).collect(),
+        };
+        self.shift(&delta, true);
+
+        let robot_moved = !delta.moved.is_empty();
+        self.history.push(delta);
+        self.step += 1;
+        robot_moved
+    }
+
+    fn undo(&mut self) -> bool {
+        if self.step == 0 {
+            return false;
+        }
+        self.step -= 1;
+        let delta = self.history[self.step].clone();
+        self.shift(&delta, false);
+        true
+    }
+
+    fn redo(&mut self) -> bool {
+        let Some(delta) = self.history.get(self.step).cloned() else {
+            return false;
+        };
+        self.shift(&delta, true);
+        self.step += 1;
+        true
+    }
+
+    // Undoes or redoes moves until `step`, as far as the history goes
+    fn seek(&mut self, step: usize) {
+        while self.step > step && self.undo() {}
+        while self.step < step && self.redo() {}
+    }
+}
+
+// Sizes and densities for `generate`, the densities in percent of the cells
+// inside the border
+#[derive(Debug, Clone, Copy)]
+struct Generator {
+    height: usize,
+    width: usize,
+    walls: u64,
+    boxes: u64,
+    instructions: usize,
+    // Height and width of each cell, like in `scale`
+    scale: (usize, usize),
+}
+
+impl Generator {
+    // Single width warehouse with a wall around, some walls and boxes inside
+    // and the robot somewhere, and random instructions. Then scaled, the
+    // boxes become objects when they don't fit in `[]`.
+    fn generate(&self, rng: &mut Rng) -> Input {
+        let (height, width) = (self.height.max(3), self.width.max(3));
+        let mut grid = Grid::new(width, height, Wall);
+
+        for i in 1 .. height - 1 {
+            for j in 1 .. width - 1 {
+                grid[i][j] = if rng.chance(self.walls) {
+                    Wall
+                } else if rng.chance(self.boxes) {
+                    SmallBox
                 } else {
-                    pos
-                }
+                    Empty
+                };
             }
-            
-        },
-        
-        Robot | SmallBox => {
-            let tpos = target_pos(pos, instruction);
-
-            // Try to move the target position
-            move_elem(grid, tpos, instruction);
-            
-            // Check if target pos is empty
-            let (ti, tj) = tpos;
-            if grid[ti][tj] == Empty {
-                grid[ti][tj] = grid[i][j];
-                grid[i][j] = Empty;
-                tpos
-            } else {
-                pos
+        }
+        let robot = (1 + rng.below(height as u64 - 2) as usize, 1 + rng.below(width as u64 - 2) as usize);
+        grid[robot] = Robot;
+
+        let instructions = (0 .. self.instructions)
+            .map(|_| [MoveN, MoveS, MoveW, MoveE][rng.below(4) as usize])
+            .collect();
+        (scale(&grid, self.scale.0, self.scale.1), instructions)
+    }
+}
+
+// Checks what no move can change: the walls, the number of each kind of
+// box, a single robot that moves at most one cell, wide boxes in one piece
+// and objects with the same cells in one piece. Returns the first rule
+// broken.
+fn check_move(before: &Grid, after: &Grid) -> Result<(), String> {
+    if (before.height(), before.width()) != (after.height(), after.width()) {
+        return Err(String::from("the grid changed size"));
+    }
+
+    let count = |grid: &Grid, elem: GridElem| grid.find_all(&elem).count();
+    for elem in [SmallBox, BoxLeft, BoxRight] {
+        if count(before, elem) != count(after, elem) {
+            return Err(format!("{elem:?} count went from {} to {}", count(before, elem), count(after, elem)));
+        }
+    }
+    if count(after, Robot) != 1 {
+        return Err(format!("{} robots", count(after, Robot)));
+    }
+
+    for ((i, j), elem) in after.iter() {
+        if (*elem == Wall) != (before[i][j] == Wall) {
+            return Err(format!("wall changed at {:?}", (i, j)));
+        }
+        if *elem == BoxLeft && after.get(i as i32, j as i32 + 1) != Some(&BoxRight) {
+            return Err(format!("`[` without `]` at {:?}", (i, j)));
+        }
+        if *elem == BoxRight && (j == 0 || after[i][j - 1] != BoxLeft) {
+            return Err(format!("`]` without `[` at {:?}", (i, j)));
+        }
+    }
+
+    let objects = |grid: &Grid| {
+        let mut cells = BTreeMap::<u32, Vec<(usize, usize)>>::new();
+        for (pos, elem) in grid.iter() {
+            if let Object(id) = elem {
+                cells.entry(*id).or_default().push(pos);
             }
         }
+        cells
+    };
+    let (objects_before, objects_after) = (objects(before), objects(after));
+    for (id, cells) in &objects_before {
+        let count = objects_after.get(id).map_or(0, |cells| cells.len());
+        if count != cells.len() {
+            return Err(format!("object {id} went from {} to {count} cells", cells.len()));
+        }
+        if object_cells(after, objects_after[id][0]).len() != count {
+            return Err(format!("object {id} split at {:?}", objects_after[id][0]));
+        }
+    }
+    if let Some(id) = objects_after.keys().find(|id| !objects_before.contains_key(id)) {
+        return Err(format!("object {id} appeared"));
     }
+
+    let (from, to) = (find_robot(before).unwrap(), find_robot(after).unwrap());
+    if from.0.abs_diff(to.0) + from.1.abs_diff(to.1) > 1 {
+        return Err(format!("robot jumped from {from:?} to {to:?}"));
+    }
+    Ok(())
 }
 
+// Every box counts at its top left cell. Objects of any shape count once,
+// at their top row and the left cell in it.
 fn score(grid: &Grid) -> u32 {
+    let gps = |(i, j): (usize, usize)| 100 * i as u32 + j as u32;
+    let mut objects = BTreeMap::<u32, Vec<(usize, usize)>>::new();
     let mut result = 0;
-    for i in 0 .. grid.len() {
-        for j in 0 .. grid[i].len() {
-            if grid[i][j] == SmallBox || grid[i][j] == BoxLeft {
-                let i = i as u32;
-                let j = j as u32;
-                result += 100 * i + j
-            }
+
+    for (pos, elem) in grid.iter() {
+        match elem {
+            SmallBox | BoxLeft => result += gps(pos),
+            Object(id) => objects.entry(*id).or_default().push(pos),
+            _ => {}
         }
     }
-    result
+    result + objects.values().filter_map(|cells| cells.iter().min().copied()).map(gps).sum::<u32>()
 }
 
 #[aoc(day15, part1)]
 fn part1((grid, instructions): &Input) -> u32 {
-    let mut grid = grid.clone();
-    let mut robot = find_robot(&grid).unwrap();
-    
-    for i in instructions {
-        robot = move_elem(&mut grid, robot, i);
-    }
-    score(&grid)
+    Warehouse::run(grid, instructions).unwrap().score()
 }
 
 // #[aoc(day15, part1)]
 fn part1_vis((grid, instructions): &Input) -> u32 {
     let mut grid = grid.clone();
     let mut robot = find_robot(&grid).unwrap();
+    let mut player = Player::terminal().fps(4.0);
 
-    print!("\x1Bc");
-    println!("INIT");
-    print_grid(&grid);
-    thread::sleep(time::Duration::from_millis(250));
+    player.show(&grid_frame(&grid).caption(String::from("INIT"))).unwrap();
     
     for i in instructions {
         robot = move_elem(&mut grid, robot, i);
-        print!("\x1Bc");
-        println!(" {:?}", i);
-        print_grid(&grid);
-        thread::sleep(time::Duration::from_millis(250));
+        if !player.show(&grid_frame(&grid).caption(format!(" {:?}", i))).unwrap() {
+            break;
+        }
     }
 
     score(&grid)
@@ -325,40 +601,104 @@ fn part1_vis((grid, instructions): &Input) -> u32 {
 
 #[aoc(day15, part2)]
 fn part2((grid, instructions): &Input) -> u32 {
-    let mut grid = grid.clone();
-    let mut robot = find_robot(&grid).unwrap();
-    
-    for i in instructions {
-        robot = move_elem(&mut grid, robot, i);
-    }
-
-    print_grid(&grid);
-    
-    score(&grid)
+    let warehouse = Warehouse::run(grid, instructions).unwrap();
+    print_grid(warehouse.grid());
+    warehouse.score()
 }
 
 // #[aoc(day15, part2)]
 fn part2_vis((grid, instructions): &Input) -> u32 {
     let mut grid = grid.clone();
     let mut robot = find_robot(&grid).unwrap();
+    let mut player = Player::terminal().fps(5.0);
 
-    print!("\x1Bc");
-    println!("INIT");
-    print_grid(&grid);
-    thread::sleep(time::Duration::from_millis(200));
+    player.show(&grid_frame(&grid).caption(String::from("INIT"))).unwrap();
     
     for idx in 0 .. instructions.len() {
         let i = instructions[idx];
         robot = move_elem(&mut grid, robot, &i);
-        print!("\x1Bc");
-        println!(" {}/{} {:?}", idx + 1, instructions.len(), i);
-        print_grid(&grid);
-        thread::sleep(time::Duration::from_millis(200));
+        let caption = format!(" {}/{} {:?}", idx + 1, instructions.len(), i);
+        if !player.show(&grid_frame(&grid).caption(caption)).unwrap() {
+            break;
+        }
     }
 
     score(&grid)
 }
 
+fn key_instruction(key: Key) -> Option<Instruction> {
+    match key {
+        Key::Up | Key::Char('w' | 'W') => Some(MoveN),
+        Key::Down | Key::Char('s' | 'S') => Some(MoveS),
+        Key::Left | Key::Char('a' | 'A') => Some(MoveW),
+        Key::Right | Key::Char('d' | 'D') => Some(MoveE),
+        _ => None,
+    }
+}
+
+// Moves the robot with the arrows or WASD until `q`, Ctrl-C or the end of
+// the keys, showing the grid after each one. `u` and `r` undo and redo
+// moves. Returns the warehouse at the last step played.
+fn play(grid: &Grid, keys: impl BufRead, player: &mut Player) -> io::Result<Warehouse> {
+    let mut warehouse = Warehouse::new(grid).unwrap();
+
+    let show = |warehouse: &Warehouse, player: &mut Player| {
+        let caption = format!(
+            "GPS {}, {} moves (arrows or WASD to move, u/r to undo/redo, q to quit)",
+            warehouse.score(),
+            warehouse.step()
+        );
+        player.show(&grid_frame(warehouse.grid()).caption(caption))
+    };
+    show(&warehouse, player)?;
+
+    for key in viz::keys(keys) {
+        let key = key?;
+        match key {
+            Key::Char('q' | 'Q' | '\x03') => break,
+            Key::Char('u' | 'U') => {
+                warehouse.undo();
+            }
+            Key::Char('r' | 'R') => {
+                warehouse.redo();
+            }
+            _ => match key_instruction(key) {
+                Some(instruction) => {
+                    warehouse.apply(instruction);
+                }
+                None => continue,
+            },
+        }
+        show(&warehouse, player)?;
+    }
+    Ok(warehouse)
+}
+
+fn play_terminal(grid: &Grid) -> io::Result<Warehouse> {
+    let _keys = KeyMode::enable();
+    play(grid, io::stdin().lock(), &mut Player::terminal().no_wait())
+}
+
+// Plays the warehouse of a puzzle input with every cell made `height` x
+// `width` cells, part 2 is 1x2. Run by `aoc-2024 play`. With `save` writes
+// the map before scaling and the moves played as a puzzle input, which
+// reads back with `parse`, or `parse_double` after playing at 1x2.
+pub(crate) fn play_input(
+    text: &str,
+    (height, width): (usize, usize),
+    save: Option<&Path>,
+) -> Result<u32, Box<dyn Error>> {
+    let (grid, _) = parse(text)?;
+    let warehouse = play_terminal(&scale(&grid, height, width))?;
+
+    if let Some(path) = save {
+        let moves = warehouse.instructions();
+        let count = moves.len();
+        write_text(path, &(grid, moves))?;
+        println!("{count} moves saved to {}", path.display());
+    }
+    Ok(warehouse.score())
+}
 
 #[cfg(test)]
 mod tests {
@@ -377,10 +717,10 @@ mod tests {
              ########
              
              <^^>>>vv<v>>v<<
-             <^^>>>vv<v>>v<<");
+             <^^>>>vv<v>>v<<").unwrap();
 
-        assert_eq!(grid.len(), 8);
-        assert_eq!(grid[0].len(), 8);
+        assert_eq!(grid.height(), 8);
+        assert_eq!(grid.width(), 8);
         assert_eq!(grid[0][0], Wall);
         assert_eq!(grid[1][1], Empty);
         assert_eq!(grid[1][3], SmallBox);
@@ -393,6 +733,18 @@ mod tests {
         assert_eq!(instructions[6], MoveS);
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("#####\n#@.X#\n#####\n\n<<").unwrap_err();
+        assert_eq!(err, ParseError::new(15, 2, 4, "one of `#.O[]@`", "X"));
+
+        let err = parse_double("#####\n#@.O#\n#####\n\n<<>x").unwrap_err();
+        assert_eq!(err, ParseError::new(15, 5, 4, "one of `<>^v`", "x"));
+
+        let err = parse("#####\n#..O#\n#####\n\n<<").unwrap_err();
+        assert_eq!(err.expected, "a map with a robot `@`");
+    }
+
     #[test]
     fn test_move_simple() {
         let (mut grid, instructions) = parse(
@@ -401,7 +753,7 @@ mod tests {
              #.@.O..#
              #...O..#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveN);
@@ -433,7 +785,7 @@ mod tests {
              #...O@.#
              #......#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveW);
@@ -451,7 +803,7 @@ mod tests {
              #..[]@.#
              #......#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveW);
@@ -469,7 +821,7 @@ mod tests {
              #.@[]..#
              #......#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveE);
@@ -488,7 +840,7 @@ mod tests {
              #..[]..#
              #..@...#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveN);
@@ -513,7 +865,7 @@ mod tests {
              #..@...#
              #..[]..#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveS);
@@ -538,7 +890,7 @@ mod tests {
              #..[]..#
              #.[][].#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveS);
@@ -573,7 +925,7 @@ mod tests {
              #..[]..#
              #...[].#
              #..[]..#
-             ########");
+             ########").unwrap();
 
         print_grid(&grid);
         let mut pos = find_robot(&grid).unwrap();
@@ -597,7 +949,7 @@ mod tests {
              #.[][].#
              #..[]..#
              #..@...#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveN);
@@ -631,7 +983,7 @@ mod tests {
              #.OOO@.#
              #......#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveW);
@@ -651,7 +1003,7 @@ mod tests {
              #OOOO@.#
              #......#
              #......#
-             ########");
+             ########").unwrap();
 
         let mut pos = find_robot(&grid).unwrap();
         pos = move_elem(&mut grid, pos, &MoveW);
@@ -677,7 +1029,7 @@ mod tests {
              ########
              
              <^^>>>vv<v>>v<<"
-        );
+        ).unwrap();
 
         let mut robot = find_robot(&grid).unwrap();
         for i in instructions {
@@ -704,10 +1056,298 @@ mod tests {
              #...O..#
              #...O..#
              ########"
-        );
+        ).unwrap();
         assert_eq!(score(&grid), 2028);
     }
 
+    #[test]
+    fn test_warehouse() {
+        let (grid, instructions) = parse(
+            "########
+             #..O.O.#
+             ##@.O..#
+             #...O..#
+             #.#.O..#
+             #...O..#
+             #......#
+             ########
+
+             <^^>>>vv<v>>v<<"
+        ).unwrap();
+
+        let mut warehouse = Warehouse::run(&grid, &instructions).unwrap();
+        assert_eq!((warehouse.step(), warehouse.len()), (15, 15));
+        assert_eq!(warehouse.robot(), (4, 4));
+        assert_eq!(warehouse.score(), 2028);
+
+        while warehouse.undo() {}
+        assert_eq!(warehouse.step(), 0);
+        assert_eq!(warehouse.grid(), &grid);
+
+        while warehouse.redo() {}
+        assert_eq!(warehouse.score(), 2028);
+
+        // A new move drops the undone ones
+        warehouse.seek(3);
+        assert!(!warehouse.apply(MoveN));
+        assert_eq!((warehouse.step(), warehouse.len()), (4, 4));
+        assert!(!warehouse.redo());
+    }
+
+    #[test]
+    fn test_warehouse_seek() {
+        let (grid, instructions) = parse_double(
+            "#######
+             #...#.#
+             #.....#
+             #..OO@#
+             #..O..#
+             #.....#
+             #######
+
+             <vv<<^^<<^^"
+        ).unwrap();
+
+        let mut warehouse = Warehouse::run(&grid, &instructions).unwrap();
+        for step in [4, 11, 0, 7, 2, 9] {
+            warehouse.seek(step);
+
+            let mut expected = grid.clone();
+            let mut robot = find_robot(&expected).unwrap();
+            for i in &instructions[.. step] {
+                robot = move_elem(&mut expected, robot, i);
+            }
+            assert_eq!(warehouse.grid(), &expected, "step {step}");
+            assert_eq!(warehouse.robot(), robot);
+            assert_eq!(warehouse.score(), score(&expected));
+        }
+    }
+
+    #[test]
+    fn test_play() {
+        let (grid, _) = parse_double(
+            "#####
+             #.O@#
+             #...#
+             #####"
+        ).unwrap();
+
+        let path = std::env::temp_dir().join("aoc-2024-day15-play.txt");
+        let mut player = Player::to_file(&path).unwrap();
+        // Esc doesn't quit, Ctrl-C does
+        let keys: &[u8] = b"a\x1b[Dxs\x1b[Cuu\x1bxr\x03<<";
+        let warehouse = play(&grid, keys, &mut player).unwrap();
+        drop(player);
+
+        assert_eq!(format_instructions(&warehouse.instructions()), "<<v");
+        let text = fs::read_to_string(&path).unwrap();
+        let last = text.split("\n\n").last().unwrap();
+        assert_eq!(last, "GPS 102, 3 moves (arrows or WASD to move, u/r to undo/redo, q to quit)
+##########
+##[]....##
+##..@...##
+##########
+");
+    }
+
+    #[test]
+    fn test_scale() {
+        let map = "#######\n#...#.#\n#.....#\n#..OO@#\n#..O..#\n#.....#\n#######";
+        let (grid, _) = parse(map).unwrap();
+        assert_eq!(scale(&grid, 1, 1), grid);
+        assert_eq!(scale(&grid, 1, 2), parse_double(map).unwrap().0);
+
+        let grid = scale(&parse("####\n#O@#\n####").unwrap().0, 2, 3);
+        assert_eq!((grid.height(), grid.width()), (6, 12));
+        assert_eq!(grid[2][3 .. 9], [Object(0), Object(0), Object(0), Robot, Empty, Empty]);
+        assert_eq!(grid[3][3 .. 9], [Object(0), Object(0), Object(0), Empty, Empty, Empty]);
+        assert_eq!(object_cells(&grid, (3, 4)).len(), 6);
+        assert_eq!(score(&grid), 203);
+    }
+
+    #[test]
+    fn test_move_objects() {
+        // Triple width boxes, the lower one pushes both upper ones
+        let (grid, _) = parse(
+            "#####
+             #...#
+             #OO.#
+             #...#
+             #..@#
+             #####"
+        ).unwrap();
+        let mut grid = scale(&grid, 1, 3);
+        for j in 5 .. 8 {
+            grid[3][j] = Object(7);
+        }
+        grid[4][9] = Empty;
+        grid[4][6] = Robot;
+
+        let robot = move_elem(&mut grid, (4, 6), &MoveN);
+        assert_eq!(robot, (3, 6));
+        assert_eq!(grid[2][4 .. 9], [Empty, Object(7), Object(7), Object(7), Empty]);
+        assert_eq!(grid[1][3 .. 10], [Object(0), Object(0), Object(0), Object(1), Object(1), Object(1), Empty]);
+
+        // Now the upper boxes touch the wall
+        let robot = move_elem(&mut grid, robot, &MoveN);
+        assert_eq!(robot, (3, 6));
+        assert_eq!(score(&grid), 103 + 106 + 205);
+
+        // A 2x2 box pushed sideways moves both rows
+        let mut grid = scale(&parse("#####\n#.O@#\n#####").unwrap().0, 2, 2);
+        let robot = move_elem(&mut grid, (2, 6), &MoveW);
+        assert_eq!(robot, (2, 5));
+        assert_eq!(grid[2][2 .. 6], [Empty, Object(0), Object(0), Robot]);
+        assert_eq!(grid[3][2 .. 6], [Empty, Object(0), Object(0), Empty]);
+    }
+
+    #[test]
+    fn test_to_text() {
+        let text = "#######\n#...#.#\n#.....#\n#..OO@#\n#..O..#\n#.....#\n#######\n\n<vv<<^^<<^^\n";
+        let input = parse(text).unwrap();
+        assert_eq!(to_text(&input).unwrap(), text);
+
+        let (grid, instructions) = parse_double(text).unwrap();
+        assert_eq!(
+            format_map(&grid),
+            "##############\n##......##..##\n##..........##\n##....[][]@.##\n\
+             ##....[]....##\n##..........##\n##############\n"
+        );
+
+        // Long instruction lists are wrapped
+        let instructions = vec![MoveE; INSTRUCTIONS_WIDTH + 2];
+        let text = to_text(&(input.0.clone(), instructions)).unwrap();
+        assert!(text.ends_with(&format!("\n\n{}\n>>\n", ">".repeat(INSTRUCTIONS_WIDTH))));
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let text = "##########\n#..O..O.O#\n#......O.#\n#.OO..O.O#\n#..O@..O.#\n\
+                    #O#..O...#\n#O..O..O.#\n#.OO.O.OO#\n#....O...#\n##########\n\n\
+                    <vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^\n";
+
+        let input = parse(text).unwrap();
+        assert_eq!(parse(&to_text(&input).unwrap()).unwrap(), input);
+
+        // A double width grid written as is reads back with `parse`, the
+        // single width one with `parse_double`
+        let double = parse_double(text).unwrap();
+        assert_eq!(parse(&to_text(&double).unwrap()).unwrap(), double);
+        assert_eq!(parse_double(&to_text(&input).unwrap()).unwrap(), double);
+    }
+
+    #[test]
+    fn test_write_text() {
+        let (grid, _) = parse("#####\n#.O@#\n#...#\n#####").unwrap();
+        let input = (grid, vec![MoveW, MoveS, MoveE]);
+        let path = std::env::temp_dir().join("aoc-2024-day15-write.txt");
+
+        write_text(&path, &input).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(parse(&text).unwrap(), input);
+        assert_eq!(parse_double(&text).unwrap(), (scale(&input.0, 1, 2), input.1.clone()));
+
+        // Objects can't be written
+        let err = write_text(&path, &(scale(&input.0, 2, 2), input.1)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_generate() {
+        let generator = Generator { height: 8, width: 10, walls: 10, boxes: 30, instructions: 50, scale: (1, 1) };
+        let input = generator.generate(&mut Rng::new(15));
+        assert_eq!(generator.generate(&mut Rng::new(15)), input);
+        assert_ne!(generator.generate(&mut Rng::new(16)), input);
+
+        let (grid, instructions) = &input;
+        assert_eq!((grid.height(), grid.width(), instructions.len()), (8, 10, 50));
+        assert!(grid.row(0).chain(grid.row(7)).chain(grid.column(0)).chain(grid.column(9)).all(|e| *e == Wall));
+        assert_eq!(grid.find_all(&Robot).count(), 1);
+
+        // Valid puzzle inputs for both rules
+        let text = to_text(&input).unwrap();
+        assert_eq!(parse(&text).unwrap(), input);
+        assert_eq!(parse_double(&text).unwrap().0, scale(grid, 1, 2));
+
+        let scaled = Generator { scale: (2, 3), ..generator }.generate(&mut Rng::new(15));
+        assert_eq!(scaled, (scale(grid, 2, 3), input.1));
+    }
+
+    #[test]
+    fn test_check_move() {
+        let (grid, _) = parse("#####\n#@[]#\n#####").unwrap();
+        assert_eq!(check_move(&grid, &grid), Ok(()));
+
+        let mut broken = grid.clone();
+        broken[1][3] = Empty;
+        assert_eq!(check_move(&grid, &broken), Err(String::from("BoxRight count went from 1 to 0")));
+
+        let mut broken = grid.clone();
+        broken[1][2] = BoxRight;
+        broken[1][3] = BoxLeft;
+        assert_eq!(check_move(&grid, &broken), Err(String::from("`]` without `[` at (1, 2)")));
+
+        let grid = scale(&parse("######\n#@O..#\n######").unwrap().0, 2, 2);
+        assert_eq!(check_move(&grid, &grid), Ok(()));
+
+        let mut broken = grid.clone();
+        broken[3][5] = Empty;
+        assert_eq!(check_move(&grid, &broken), Err(String::from("object 0 went from 4 to 3 cells")));
+
+        broken[3][8] = Object(0);
+        assert_eq!(check_move(&grid, &broken), Err(String::from("object 0 split at (2, 4)")));
+
+        let mut broken = grid.clone();
+        broken[2][8] = Object(1);
+        assert_eq!(check_move(&grid, &broken), Err(String::from("object 1 appeared")));
+    }
+
+    #[test]
+    fn test_random_warehouses() {
+        let mut rng = Rng::new(2024);
+
+        for case in 0 .. 100 {
+            let generator = Generator {
+                height: 3 + rng.below(10) as usize,
+                width: 3 + rng.below(10) as usize,
+                walls: rng.below(30),
+                boxes: rng.below(60),
+                instructions: 100,
+                scale: (1, 1),
+            };
+            let text = to_text(&generator.generate(&mut rng)).unwrap();
+            let scale = (1 + rng.below(3) as usize, 1 + rng.below(3) as usize);
+            // Fewer moves, checking the objects is slower
+            let scaled = Generator { scale, instructions: 30, ..generator }.generate(&mut rng);
+
+            let inputs = [
+                (parse(&text).unwrap(), part1 as fn(&Input) -> u32),
+                (parse_double(&text).unwrap(), part2),
+                (scaled, part1),
+            ];
+            for (input, part) in inputs {
+                let (start, instructions) = &input;
+                let mut grid = start.clone();
+                let mut robot = find_robot(&grid).unwrap();
+
+                for (step, i) in instructions.iter().enumerate() {
+                    let before = grid.clone();
+                    robot = move_elem(&mut grid, robot, i);
+                    if let Err(e) = check_move(&before, &grid) {
+                        panic!("case {case}, move {step} {i:?}: {e}\n{}", format_map(start));
+                    }
+                }
+
+                assert_eq!(part(&input), score(&grid), "case {case}\n{}", format_map(start));
+                let mut warehouse = Warehouse::run(start, instructions).unwrap();
+                assert_eq!(warehouse.grid(), &grid);
+                warehouse.seek(0);
+                assert_eq!(warehouse.grid(), start);
+            }
+        }
+    }
+
     #[test]
     fn test_part1_small() {
         let input = parse(
@@ -721,9 +1361,29 @@ mod tests {
              ########
              
              <^^>>>vv<v>>v<<"
-        );
+        ).unwrap();
         assert_eq!(part1(&input), 2028);
     }
+
+    #[test]
+    fn test_record_moves() {
+        let input = parse(
+            "########
+             #..O.O.#
+             ##@.O..#
+             #...O..#
+             #.#.O..#
+             #...O..#
+             #......#
+             ########
+
+             <^^>>>vv<v>>v<<"
+        ).unwrap();
+
+        let mut recorder = Recorder::new(4);
+        record_moves(&input, &mut recorder).unwrap();
+        assert_eq!(recorder.len(), 16);
+    }
     
     #[test]
     fn test_part1_large() {
@@ -749,7 +1409,7 @@ mod tests {
              <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
              ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
              v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^"
-        );
+        ).unwrap();
 
 
         assert_eq!(part1(&input), 10092);
@@ -768,10 +1428,35 @@ mod tests {
              ##..@......[].[][]##
              ##......[][]..[]..##
              ####################"
-        );
+        ).unwrap();
         assert_eq!(score(&grid), 9021);
     }
 
+    #[test]
+    fn test_score_objects() {
+        // An L, counted once at (1, 2), and a box
+        let mut grid = Grid::new(4, 3, Empty);
+        for pos in [(1, 2), (2, 1), (2, 2)] {
+            grid[pos] = Object(0);
+        }
+        grid[(0, 3)] = Object(1);
+        assert_eq!(score(&grid), 102 + 3);
+    }
+
+    #[test]
+    fn test_no_border() {
+        // The grid ends where the map does, the box can't leave it
+        let input = parse("@.O\n...\n\n^<>>>").unwrap();
+        let warehouse = Warehouse::run(&input.0, &input.1).unwrap();
+        assert_eq!(warehouse.robot(), (0, 1));
+        assert_eq!(warehouse.score(), 2);
+
+        let input = parse_double("@.O\n...\n\n^<>>>>").unwrap();
+        let warehouse = Warehouse::run(&input.0, &input.1).unwrap();
+        assert_eq!(warehouse.robot(), (0, 3));
+        assert_eq!(part2(&input), 4);
+    }
+
     #[test]
     fn test_part2_large() {
         let input = parse_double(
@@ -796,7 +1481,7 @@ mod tests {
              <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
              ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
              v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^"
-        );
+        ).unwrap();
 
 
         assert_eq!(part2(&input), 9021);
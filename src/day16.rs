@@ -1,9 +1,12 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
-use std::collections::{HashSet, HashMap};
-use colored::Colorize;
+use std::collections::HashSet;
+use crate::utils::Grid;
+use crate::viz::{self, GridFrame, Style};
+use crate::parse::ParseError;
+use crate::search;
 
-type Input = Vec<Vec<char>>;
+type Input = Grid<char>;
 
 #[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
 enum Dir {
@@ -13,60 +16,26 @@ enum Dir {
 use Dir::*;
     
 #[aoc_generator(day16)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| l.trim().chars().collect())
-        .collect()
-}
-
-fn start_position(input: &Input) -> (usize, usize) {
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
-            if input[i][j] == 'S' {
-                return (i, j);
-            }
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let grid = Grid::try_parse(16, input, "one of `.#SE`", |c| match c {
+        '.' | '#' | 'S' | 'E' => Some(c),
+        _ => None,
+    })?;
+
+    for c in ['S', 'E'] {
+        if grid.find(&c).is_none() {
+            return Err(ParseError::eof(16, input, &format!("a map with `{c}`")));
         }
     }
-    panic!("Map without start is not valid");
+    Ok(grid)
 }
 
-fn end_position(input: &Input) -> (usize, usize) {
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
-            if input[i][j] == 'E' {
-                return (i, j);
-            }
-        }
-    }
-    panic!("Map without end is not valid");
+fn start_position(input: &Input) -> (usize, usize) {
+    input.find(&'S').expect("Map without start is not valid")
 }
 
-fn neighbours_prev(_input: &Input, (i, j, dir): (usize, usize, Dir)) -> Vec<((usize, usize, Dir), u32)> {
-    let mut result = Vec::new();
-    match dir {
-        N => {
-            result.push(((i+1, j, N), 1));
-            result.push(((i, j, E), 1000));
-            result.push(((i, j, W), 1000));
-        }
-        S => {
-            result.push(((i-1, j, S), 1));
-            result.push(((i, j, E), 1000));
-            result.push(((i, j, W), 1000));
-        }
-        E => {
-            result.push(((i, j-1, E), 1));
-            result.push(((i, j, N), 1000));
-            result.push(((i, j, S), 1000));
-        }
-        W => {
-            result.push(((i, j+1, W), 1));
-            result.push(((i, j, N), 1000));
-            result.push(((i, j, S), 1000));
-        }
-    };
-    result
+fn end_position(input: &Input) -> (usize, usize) {
+    input.find(&'E').expect("Map without end is not valid")
 }
 
 fn neighbours(input: &Input, (i, j, dir): (usize, usize, Dir)) -> Vec<((usize, usize, Dir), u32)> {
@@ -81,14 +50,14 @@ fn neighbours(input: &Input, (i, j, dir): (usize, usize, Dir)) -> Vec<((usize, u
         }
 
         S => {
-            if i < input.len() - 1 && input[i+1][j] != '#' {
+            if i < input.height() - 1 && input[i+1][j] != '#' {
                 result.push(((i + 1, j, S), 1))
             }
             result.push(((i, j, W), 1000));
             result.push(((i, j, E), 1000));
         }
         E => {
-            if j < input[i].len() - 1 && input[i][j+1] != '#' {
+            if j < input.width() - 1 && input[i][j+1] != '#' {
                 result.push(((i, j+1, E), 1))
             }
             result.push(((i, j, S), 1000));
@@ -105,108 +74,40 @@ fn neighbours(input: &Input, (i, j, dir): (usize, usize, Dir)) -> Vec<((usize, u
     result
 }
 
-fn build_path(from_node: &HashMap::<(usize, usize, Dir), (usize, usize, Dir)>, last: &(usize, usize, Dir)) -> Vec<(usize, usize, Dir)> {
-    let mut result = Vec::from([*last]);
-    let mut current = last;
-
-    while let Some(val) = from_node.get(current) {
-        result.push(*val);
-        current = val
-    }
-
-    result.reverse();
-    result
-}
-
 fn search_path(input: &Input, start: (usize, usize), end: (usize, usize)) -> Vec<(usize, usize, Dir)> {
     let mut all_points = HashSet::<(usize, usize)>::new();
     search_path_aux(input, start, end, &mut all_points)
 }
 
-// Implements A* algorithm for the problem
+// Dijkstra over (position, direction). Fills `all_nodes` with the positions
+// of every best path and returns one of them.
 fn search_path_aux(input: &Input, start: (usize, usize), end: (usize, usize), all_nodes: &mut HashSet<(usize, usize)>) -> Vec<(usize, usize, Dir)> {
     let start = (start.0, start.1, E);
-
-    let mut open = HashSet::<(usize, usize, Dir)>::from([start]);
-    let mut from_node = HashMap::<(usize, usize, Dir), (usize, usize, Dir)>::new();
-
-    let mut gscore = HashMap::<(usize, usize, Dir), u32>::new();
-    gscore.insert(start, 0);
-
-    while !open.is_empty() {
-        let current = *open.iter().next().unwrap();
-        open.remove(&current);
-
-        let current_score = *gscore.get(&current).unwrap();
-        let neighbours = neighbours(input, current);
-
-        for (neighbour, score) in neighbours {
-            let tentative_score = current_score + score;
-            
-            if tentative_score < *gscore.get(&neighbour).unwrap_or(&u32::MAX) {
-                from_node.insert(neighbour, current);
-                gscore.insert(neighbour, tentative_score);
-                if !open.contains(&neighbour) {
-                    open.insert(neighbour);
-                }
-            }
-        }
-    }
-
-    all_nodes.insert(end);
-    all_nodes.insert((start.0, start.1));
+    let paths = search::dijkstra_all(start, |&n| neighbours(input, n));
 
     let end_nodes: Vec<_> = [N, S, W, E]
         .iter()
         .map(|d| (end.0, end.1, *d))
+        .filter(|n| paths.cost(n).is_some())
         .collect();
-    
+
     let min_end_score = end_nodes
         .iter()
-        .map(|n| gscore.get(&n).unwrap_or(&10000000))
-        .min().unwrap();
+        .filter_map(|n| paths.cost(n))
+        .min()
+        .expect("End is not reachable");
 
-
-    let min_end: Vec<(usize,usize,Dir)> = end_nodes
-        .iter()
-        .filter(|n| gscore.get(&n).unwrap_or(&10000000) == min_end_score)
-        .map(|n| (n.0, n.1, n.2))
+    let min_end: Vec<(usize, usize, Dir)> = end_nodes
+        .into_iter()
+        .filter(|n| paths.cost(n) == Some(min_end_score))
         .collect();
 
-    let mut pending = Vec::from(min_end);
-
-    while let Some(current) = pending.pop() {
-        let nns = neighbours_prev(input, current);
-        
-        let min = nns
-            .iter()
-            .map(|(n, s)| gscore.get(n).unwrap_or(&10000000) + s)
-            .min().unwrap();
-
-        let path_nodes: Vec<_> = nns
-            .iter()
-            .filter(|(n,s)| gscore.get(n).unwrap_or(&10000000) + s == min)
-            .collect();
-        
-        path_nodes.iter().for_each(|(n, _)| {
-            if *n != start {
-                all_nodes.insert((n.0, n.1));
-                pending.push(*n);
-            } 
-        });
-    }
+    all_nodes.extend(paths.nodes(&min_end).iter().map(|&(i, j, _)| (i, j)));
 
-    let end_node = end_nodes.iter().min_by(|a,b| {
-        let s1 = gscore.get(a).unwrap_or(&10000000);
-        let s2 = gscore.get(b).unwrap_or(&10000000);
-        s1.cmp(s2)
-    }).unwrap();
-    
-    let path = build_path(&from_node, &end_node);
-    path
+    paths.path(&min_end[0]).unwrap()
 }
 
-fn print_path(input: &Vec<Vec<char>>, path: &Vec<(usize, usize, Dir)>) {
+fn print_path(input: &Input, path: &Vec<(usize, usize, Dir)>) {
     let mut output = input.clone();
 
     for (i, j, dir) in path {
@@ -218,33 +119,23 @@ fn print_path(input: &Vec<Vec<char>>, path: &Vec<(usize, usize, Dir)>) {
         }
     }
 
-    for i in 0 .. output.len() {
-        for j in 0 .. output[i].len() {
-            if output[i][j] == '#' {
-                print!("{}", "#".blue());
-            } else if output[i][j] == '.' {
-                print!(" ");
-            } else {
-                print!("{}", output[i][j].to_string().red());
-            }
-        }
-        println!()
-    }
+    viz::print(&GridFrame::new(&output, |_, c| match c {
+        '#' => ('#', Style::Wall),
+        '.' => (' ', Style::Plain),
+        c => (*c, Style::Path),
+    }));
 }
 
-fn print_nodes(input: &Vec<Vec<char>>, nodes: &HashSet<(usize, usize)>) {
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
-            if input[i][j] == '#' {
-                print!("{}", "#".blue());
-            } else if nodes.contains(&(i, j)){
-                print!("{}", "O".red());
-            } else if input[i][j] == '.' {
-                print!(" ");
-            }
+fn print_nodes(input: &Input, nodes: &HashSet<(usize, usize)>) {
+    viz::print(&GridFrame::new(input, |pos, c| {
+        if *c == '#' {
+            ('#', Style::Wall)
+        } else if nodes.contains(&pos) {
+            ('O', Style::Path)
+        } else {
+            (' ', Style::Plain)
         }
-        println!()
-    }
+    }));
 }
 
 fn score_path(path: &Vec<(usize, usize, Dir)>) -> u32 {
@@ -307,14 +198,14 @@ mod tests {
              #.###.#.#.#.#.#
              #S..#.....#...#
              ###############"
-        )
+        ).unwrap()
     }
     
     #[test]
     fn test_parse() {
         let input = default_input();
-        assert_eq!(input.len(), 15);
-        assert_eq!(input[0].len(), 15);
+        assert_eq!(input.height(), 15);
+        assert_eq!(input.width(), 15);
         assert_eq!(start_position(&input), (13, 1));
         assert_eq!(end_position(&input), (1, 13));
     }
@@ -369,7 +260,7 @@ mod tests {
              #.#.#.#########.#
              #S#.............#
              #################"
-        );
+        ).unwrap();
 
         let start = start_position(&input);
         let end = end_position(&input);
@@ -404,7 +295,7 @@ mod tests {
              #.#.#.#########.#
              #S#.............#
              #################"
-        );
+        ).unwrap();
 
         assert_eq!(part1(&input), 11048);
     }
@@ -435,7 +326,7 @@ mod tests {
              #.#.#.#########.#
              #S#.............#
              #################"
-        );
+        ).unwrap();
 
         assert_eq!(part2(&input), 64);
     }
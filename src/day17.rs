@@ -1,6 +1,60 @@
+mod asm;
+mod debugger;
+mod compiler;
+mod quine;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use crate::parse::{self, Line, ParseError};
+use debugger::Debugger;
+use quine::QuineError;
 
 type Input = (u64, u64, u64, Vec<u32>);
 
+// Errors that stop the machine
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MachineError {
+    OddLength { len: usize },
+    InvalidOpcode { ins_p: usize, opcode: u32 },
+    InvalidCombo { ins_p: usize, operand: u32 },
+    ReservedOperand { ins_p: usize },
+    StepLimit(usize),
+    OutputLimit(usize),
+}
+
+impl fmt::Display for MachineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MachineError::OddLength { len } => write!(f, "the program has {len} values, the last opcode has no operand"),
+            MachineError::InvalidOpcode { ins_p, opcode } => write!(f, "invalid opcode {opcode} at {ins_p}"),
+            MachineError::InvalidCombo { ins_p, operand } => write!(f, "invalid combo operand {operand} at {ins_p}"),
+            MachineError::ReservedOperand { ins_p } => write!(f, "reserved combo operand 7 at {ins_p}"),
+            MachineError::StepLimit(steps) => write!(f, "still running after {steps} steps"),
+            MachineError::OutputLimit(values) => write!(f, "more than {values} output values"),
+        }
+    }
+}
+
+impl Error for MachineError {}
+
+// Stops programs that don't halt or print too much
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Limits {
+    pub steps: usize,
+    pub output: usize,
+}
+
+impl Limits {
+    pub const NONE: Limits = Limits { steps: usize::MAX, output: usize::MAX };
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
 struct Machine {
     reg_a: u64,
     reg_b: u64,
@@ -8,6 +62,8 @@ struct Machine {
     ins_p: usize,
     halt: bool,
     output: Vec<u32>,
+    limits: Limits,
+    steps: usize,
 }
 
 const ADV: u32 = 0;
@@ -25,29 +81,36 @@ impl Machine {
         let halt = false;
         let output = vec![];
         Self {
-           reg_a, reg_b, reg_c, ins_p, halt, output
+           reg_a, reg_b, reg_c, ins_p, halt, output, limits: Limits::NONE, steps: 0
         }
     }
 
-    fn combo(&self, val: u32) -> u64 {
+    fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn combo(&self, val: u32) -> Result<u64, MachineError> {
         match val {
-            0 ..= 3 => val as u64,
-            4 => self.reg_a,
-            5 => self.reg_b,
-            6 => self.reg_c,
-            _ => panic!("Invalid combo operator")
+            0 ..= 3 => Ok(val as u64),
+            4 => Ok(self.reg_a),
+            5 => Ok(self.reg_b),
+            6 => Ok(self.reg_c),
+            7 => Err(MachineError::ReservedOperand { ins_p: self.ins_p }),
+            _ => Err(MachineError::InvalidCombo { ins_p: self.ins_p, operand: val }),
         }
     }
 
-    fn adv(&mut self, operand: u32) {
-        let num = self.reg_a;
-        let den = 2u64.pow(self.combo(operand) as u32);
-        let result = num / den;
-        
-        // println!("ADV({num}, {den}) = {result}");
-        
-        self.reg_a = result;
-        self.ins_p += 2
+    // A / 2^combo, shifting out everything when the power overflows
+    fn divide(&self, operand: u32) -> Result<u64, MachineError> {
+        let shift = self.combo(operand)?;
+        Ok(if shift >= 64 { 0 } else { self.reg_a >> shift })
+    }
+
+    fn adv(&mut self, operand: u32) -> Result<(), MachineError> {
+        self.reg_a = self.divide(operand)?;
+        self.ins_p += 2;
+        Ok(())
     }
 
     fn bxl(&mut self, operand: u32) {
@@ -60,13 +123,14 @@ impl Machine {
         self.ins_p += 2;
     }
 
-    fn bst(&mut self, operand: u32) {
-        let a = self.combo(operand);
+    fn bst(&mut self, operand: u32) -> Result<(), MachineError> {
+        let a = self.combo(operand)?;
         let result = a % 8;
         // println!("BST({a}) = {result}");
 
         self.reg_b = result;
         self.ins_p += 2;
+        Ok(())
     }
 
     fn jnz(&mut self, operand: u32) {
@@ -89,182 +153,149 @@ impl Machine {
         self.ins_p += 2;
     }
 
-    fn out(&mut self, operand: u32) {
-        let a = self.combo(operand);
+    fn out(&mut self, operand: u32) -> Result<(), MachineError> {
+        let a = self.combo(operand)?;
         let result = a % 8;
         // println!("OUT({a}) =>> {result}");
 
         self.output.push(result as u32);
         self.ins_p += 2;
+        Ok(())
     }
 
-    fn bdv(&mut self, operand: u32) {
-        let num = self.reg_a;
-        let den = 2u32.pow(self.combo(operand) as u32) as u64;
-        let result = num / den;
-        
-        // println!("BDV({num}, {den}) = {result}");
-        
-        self.reg_b = result;
-        self.ins_p += 2
+    fn bdv(&mut self, operand: u32) -> Result<(), MachineError> {
+        self.reg_b = self.divide(operand)?;
+        self.ins_p += 2;
+        Ok(())
     }
 
-    fn cdv(&mut self, operand: u32) {
-        let num = self.reg_a;
-        let den = 2u32.pow(self.combo(operand) as u32) as u64;
-        let result = num / den;
-        
-        // println!("CDV({num}, {den}) = {result}");
-        
-        self.reg_c = result;
-        self.ins_p += 2
+    fn cdv(&mut self, operand: u32) -> Result<(), MachineError> {
+        self.reg_c = self.divide(operand)?;
+        self.ins_p += 2;
+        Ok(())
     }
 
-    fn run_inst(&mut self, inst: u32, operand: u32) {
+    fn run_inst(&mut self, inst: u32, operand: u32) -> Result<(), MachineError> {
         match inst {
-            ADV => self.adv(operand),
+            ADV => self.adv(operand)?,
             BXL => self.bxl(operand),
-            BST => self.bst(operand),
+            BST => self.bst(operand)?,
             JNZ => self.jnz(operand),
             BXC => self.bxc(operand),
-            OUT => self.out(operand),
-            BDV => self.bdv(operand),
-            CDV => self.cdv(operand),
-            _   => panic!()
+            OUT => self.out(operand)?,
+            BDV => self.bdv(operand)?,
+            CDV => self.cdv(operand)?,
+            _   => return Err(MachineError::InvalidOpcode { ins_p: self.ins_p, opcode: inst }),
         }
+        Ok(())
     }
-    
-    fn run(&mut self, program: &Vec<u32>) {
+
+    // Runs until the instruction pointer leaves the program. A jump to an
+    // odd address can leave the last opcode without operand, that halts too.
+    fn run(&mut self, program: &[u32]) -> Result<(), MachineError> {
+        if !program.len().is_multiple_of(2) {
+            return Err(MachineError::OddLength { len: program.len() });
+        }
+
         self.ins_p = 0;
+        self.steps = 0;
         self.halt = false;
         self.output = vec![];
         while !self.halt {
-            let inst = program.get(self.ins_p).unwrap();
-            let operand = program.get(self.ins_p + 1).unwrap();
+            let (Some(inst), Some(operand)) = (program.get(self.ins_p), program.get(self.ins_p + 1)) else {
+                self.halt = true;
+                break;
+            };
+
+            if self.steps == self.limits.steps {
+                return Err(MachineError::StepLimit(self.steps));
+            }
+            self.steps += 1;
 
-            self.run_inst(*inst, *operand);
+            self.run_inst(*inst, *operand)?;
 
+            if self.output.len() > self.limits.output {
+                return Err(MachineError::OutputLimit(self.limits.output));
+            }
             if self.ins_p >= program.len() {
                 self.halt = true;
             }
         }
+        Ok(())
     }
 }
 
-#[aoc_generator(day17)]
-fn parse(input: &str) -> Input {
-    let mut lines = input.lines();
-
-    let line_a: Vec<_> = lines.next().unwrap().trim().split(": ").collect();
-    let line_b: Vec<_> = lines.next().unwrap().trim().split(": ").collect();
-    let line_c: Vec<_> = lines.next().unwrap().trim().split(": ").collect();
-
-    let reg_a = line_a[1].parse().unwrap();
-    let reg_b = line_b[1].parse().unwrap();
-    let reg_c = line_c[1].parse().unwrap();
+// Reads a `<name>: <value>` line returning the value
+fn parse_field<'a>(input: &str, line: Option<Line<'a>>, name: &str) -> Result<&'a str, ParseError> {
+    let expected = format!("`{name}: <value>`");
+    let line = line.ok_or_else(|| ParseError::eof(17, input, &expected))?;
+    let parts = line.split_n(": ", 2, &expected)?;
 
-    lines.next();
+    if parts[0] != name {
+        return Err(line.error(parts[0], &format!("`{name}`")));
+    }
+    Ok(parts[1])
+}
 
-    let line_program: Vec<_> = lines.next().unwrap().trim().split(": ").collect();
-    let program = line_program[1].split(",").map(|i| i.parse::<u32>().unwrap()).collect();
+#[aoc_generator(day17)]
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let mut lines = parse::lines(17, input).filter(|l| !l.is_empty());
+
+    let mut register = |name: &str| -> Result<u64, ParseError> {
+        let line = lines.next();
+        let value = parse_field(input, line, name)?;
+        line.unwrap().parse(value, "a register value")
+    };
+
+    let reg_a = register("Register A")?;
+    let reg_b = register("Register B")?;
+    let reg_c = register("Register C")?;
+
+    let line = lines.next();
+    let values = parse_field(input, line, "Program")?;
+    let line = line.unwrap();
+
+    let program = values
+        .split(",")
+        .map(|i| {
+            let value: u32 = line.parse(i, "a 3-bit number")?;
+            if value > 7 {
+                return Err(line.error(i, "a 3-bit number"));
+            }
+            Ok(value)
+        })
+        .collect::<Result<_, _>>()?;
 
-    (reg_a, reg_b, reg_c, program)
+    Ok((reg_a, reg_b, reg_c, program))
 }
 
 #[aoc(day17, part1)]
-fn part1(input: &Input) -> String {
+fn part1(input: &Input) -> Result<String, MachineError> {
     let input: Input = input.clone();
     let (reg_a, reg_b, reg_c, program) = input;
     let mut m = Machine::new(reg_a, reg_b, reg_c);
-    m.run(&program);
+    m.run(&program)?;
 
     let out: Vec<_> = m.output.iter().map(|n| n.to_string()).collect();
-    out.join(",")
-}
-
-// Native implementation of the algoritm for test purposes
-/*
-fn alg_native(a: u64) -> Vec<u64> {
-    let mut out: Vec<u64> = Vec::new();
-    let mut a = a;
-
-    while a != 0 {
-        let mut b = a % 8;
-        b = b ^ 5;
-        let c = a / 2u64.pow(b as u32);
-        b = b ^ 6;
-        b = b ^ c;
-        out.push(b % 8);
-        a = a / 8;
-    }
-    out
+    Ok(out.join(","))
 }
-*/
-
-fn digits_to_num(digits: &Vec<u64>) -> u64{
-    let mut acc = 0;
-    let mut b = 8u64.pow(digits.len() as u32);
-    for i in digits {
-        acc += b * i;
-        b = b / 8;
-    }
-    acc
-}
-
-// I think this mostly works because of the program we're searching for
-// the output of the program change every number on base 8 numbers. So every
-// time you added a "base 8" digit it created a new output.
-// 1*8^3 + 4*8^2 + 0*8^1 + 3
-// Using this concept this algorithm makes a search for the changes in the
-// digits so they match the program input.
-fn search_number(program: Vec<u32>) -> u64 {
-    let mut pending = Vec::<Vec<u64>>::new();
-    let mut results = Vec::<u64>::new();
-
-    pending.push(Vec::<u64>::new());
-
-    while let Some(current) = pending.pop() {
-        let value = digits_to_num(&current);
 
-        let idx = program.len() - 1 - current.len();
-        let target_digit = program[idx];
+// Runs the debugger on stdin and stdout with the machine of a puzzle input,
+// run by `aoc-2024 debug`. Returns what the program printed.
+pub(crate) fn debug_input(text: &str) -> Result<String, Box<dyn Error>> {
+    let (reg_a, reg_b, reg_c, program) = parse(text)?;
+    let mut debugger = Debugger::new(Machine::new(reg_a, reg_b, reg_c), program);
 
-        for next in 0 ..= 7 {
-            // let out = alg_native(value + next);
-            let mut m = Machine::new(value+next, 0, 0);
-            m.run(&program);
-            let out = m.output;
+    debugger::repl(&mut debugger, io::stdin().lock(), io::stdout())?;
 
-            if out.len() > program.len() {
-                continue;
-            }
-
-            if out.len() > 0 && out[0] == target_digit {
-                if out.len() == program.len() {
-                    // println!("DONE!! {out:?} {program:?} {}", value + next);
-                    results.push(value + next);
-                    continue;
-                }
-                
-                let mut current = current.clone();
-                current.push(next);
-                                
-                pending.push(current);
-            }
-        }
-    }
-
-    *results.iter().min().unwrap()
+    let out: Vec<_> = debugger.machine.output.iter().map(|n| n.to_string()).collect();
+    Ok(out.join(","))
 }
 
-
 #[aoc(day17, part2)]
-fn part2(input: &Input) -> u64 {
-
-    let input: Input = input.clone();
+fn part2(input: &Input) -> Result<u64, QuineError> {
     let (_, _, _, program) = input;
-
-    search_number(program)
+    quine::solve(program)
 }
 
 /*
@@ -334,7 +365,7 @@ mod tests {
              Register C: 333
              
              Program: 0,1,5,4,3,0"
-        );
+        ).unwrap();
 
         assert_eq!(a, 729);
         assert_eq!(b, 100);
@@ -342,29 +373,74 @@ mod tests {
         assert_eq!(program, vec![0,1,5,4,3,0]);
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,9").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 5, 14, "a 3-bit number", "9"));
+
+        let err = parse("Register A: 729\nRegister X: 0").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 2, 1, "`Register B`", "Register X"));
+
+        let err = parse("Register A: 729\nRegister B: 0").unwrap_err();
+        assert_eq!(err.to_string(), "day 17 line 3 column 1: expected `Register C: <value>`, found end of input");
+    }
+
     #[test]
     fn test_combo_operand() {
         let m = Machine::new(19, 15, 14);
 
-        assert_eq!(m.combo(0), 0);
-        assert_eq!(m.combo(1), 1);
-        assert_eq!(m.combo(2), 2);
-        assert_eq!(m.combo(3), 3);
-        assert_eq!(m.combo(4), 19);
-        assert_eq!(m.combo(5), 15);
-        assert_eq!(m.combo(6), 14);
+        assert_eq!(m.combo(0), Ok(0));
+        assert_eq!(m.combo(1), Ok(1));
+        assert_eq!(m.combo(2), Ok(2));
+        assert_eq!(m.combo(3), Ok(3));
+        assert_eq!(m.combo(4), Ok(19));
+        assert_eq!(m.combo(5), Ok(15));
+        assert_eq!(m.combo(6), Ok(14));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut m = Machine::new(1, 0, 0);
+
+        assert_eq!(m.run(&[ADV, 1, OUT]), Err(MachineError::OddLength { len: 3 }));
+        assert_eq!(m.run(&[BXL, 1, OUT, 7]), Err(MachineError::ReservedOperand { ins_p: 2 }));
+        assert_eq!(m.run(&[ADV, 9]), Err(MachineError::InvalidCombo { ins_p: 0, operand: 9 }));
+        assert_eq!(m.run(&[8, 0]), Err(MachineError::InvalidOpcode { ins_p: 0, opcode: 8 }));
+        assert_eq!(
+            MachineError::ReservedOperand { ins_p: 2 }.to_string(),
+            "reserved combo operand 7 at 2"
+        );
+
+        // A jump to an odd address that leaves the operand out halts
+        assert_eq!(m.run(&[JNZ, 3, BXL, OUT]), Ok(()));
+    }
+
+    #[test]
+    fn test_limits() {
+        // Loops forever printing A
+        let program = vec![OUT, COMBO_A, JNZ, 0];
+
+        let mut m = Machine::new(1, 0, 0).limits(Limits { steps: 100, ..Limits::NONE });
+        assert_eq!(m.run(&program), Err(MachineError::StepLimit(100)));
+
+        let mut m = Machine::new(1, 0, 0).limits(Limits { output: 5, ..Limits::NONE });
+        assert_eq!(m.run(&program), Err(MachineError::OutputLimit(5)));
+        assert_eq!(m.output.len(), 6);
+
+        let mut m = Machine::new(0, 0, 0).limits(Limits { steps: 2, output: 1 });
+        assert_eq!(m.run(&program), Ok(()));
     }
 
     #[test]
     fn test_adv() {
         let mut m = Machine::new(24, 4, 0);
 
-        m.run(&vec![ADV, 3]);
+        m.run(&vec![ADV, 3]).unwrap();
         assert_eq!(m.reg_a, 3);
         assert_eq!(m.ins_p, 2);
 
         m.reg_a = 24;
-        m.run(&vec![ADV, COMBO_B]);
+        m.run(&vec![ADV, COMBO_B]).unwrap();
         assert_eq!(m.reg_a, 1);
         assert_eq!(m.ins_p, 2);
     }
@@ -373,12 +449,12 @@ mod tests {
     fn test_bxl() {
         let mut m = Machine::new(0, 10, 0);
 
-        m.run(&vec![BXL, 5]);
+        m.run(&vec![BXL, 5]).unwrap();
         assert_eq!(m.reg_b, 15);
         assert_eq!(m.ins_p, 2);
 
         m.reg_b = 7;
-        m.run(&vec![BXL, 1]);
+        m.run(&vec![BXL, 1]).unwrap();
         assert_eq!(m.reg_b, 6);
         assert_eq!(m.ins_p, 2);
     }
@@ -387,11 +463,11 @@ mod tests {
     fn test_bst() {
         let mut m = Machine::new(12, 0, 0);
 
-        m.run(&vec![BST, 3]);
+        m.run(&vec![BST, 3]).unwrap();
         assert_eq!(m.reg_b, 3);
         assert_eq!(m.ins_p, 2);
 
-        m.run(&vec![BST, COMBO_A]);
+        m.run(&vec![BST, COMBO_A]).unwrap();
         assert_eq!(m.reg_b, 4);
         assert_eq!(m.ins_p, 2);
     }
@@ -400,11 +476,11 @@ mod tests {
     fn test_jnz() {
         let mut m = Machine::new(12, 0, 0);
 
-        m.run(&vec![JNZ, 4]);
+        m.run(&vec![JNZ, 4]).unwrap();
         assert_eq!(m.ins_p, 4);
         
         m.reg_a = 0;
-        m.run(&vec![JNZ, 4]);
+        m.run(&vec![JNZ, 4]).unwrap();
         assert_eq!(m.ins_p, 2);
     }
 
@@ -412,12 +488,12 @@ mod tests {
     fn test_bxc() {
         let mut m = Machine::new(0, 10, 5);
 
-        m.run(&vec![BXC, 4]);
+        m.run(&vec![BXC, 4]).unwrap();
         assert_eq!(m.reg_b, 15);
 
         m.reg_b = 7;
         m.reg_c = 1;
-        m.run(&vec![BXC, 4]);
+        m.run(&vec![BXC, 4]).unwrap();
         assert_eq!(m.reg_b, 6);
     }
 
@@ -425,10 +501,10 @@ mod tests {
     fn test_out() {
         let mut m = Machine::new(12, 7, 5);
 
-        m.run(&vec![OUT, COMBO_A]);
+        m.run(&vec![OUT, COMBO_A]).unwrap();
         assert_eq!(m.output, vec![4]);
 
-        m.run(&vec![OUT, COMBO_B, OUT, COMBO_C]);
+        m.run(&vec![OUT, COMBO_B, OUT, COMBO_C]).unwrap();
         assert_eq!(m.output, vec![7, 5]);
     }
 
@@ -436,12 +512,12 @@ mod tests {
     fn test_cdv() {
         let mut m = Machine::new(24, 0, 4);
 
-        m.run(&vec![BDV, 3]);
+        m.run(&vec![BDV, 3]).unwrap();
         assert_eq!(m.reg_b, 3);
         assert_eq!(m.ins_p, 2);
 
         m.reg_a = 24;
-        m.run(&vec![BDV, COMBO_C]);
+        m.run(&vec![BDV, COMBO_C]).unwrap();
         assert_eq!(m.reg_b, 1);
         assert_eq!(m.ins_p, 2);
     }
@@ -450,12 +526,12 @@ mod tests {
     fn test_bdv() {
         let mut m = Machine::new(24, 4, 0);
 
-        m.run(&vec![CDV, 3]);
+        m.run(&vec![CDV, 3]).unwrap();
         assert_eq!(m.reg_c, 3);
         assert_eq!(m.ins_p, 2);
 
         m.reg_a = 24;
-        m.run(&vec![CDV, COMBO_B]);
+        m.run(&vec![CDV, COMBO_B]).unwrap();
         assert_eq!(m.reg_c, 1);
         assert_eq!(m.ins_p, 2);
     }
@@ -464,30 +540,30 @@ mod tests {
     fn test_examples() {
         // If register C contains 9, the program 2,6 would set register B to 1.
         let mut m = Machine::new(0, 0, 9);
-        m.run(&vec![2, 6]);
+        m.run(&vec![2, 6]).unwrap();
         assert_eq!(m.reg_b, 1);
         
         // If register A contains 10, the program 5,0,5,1,5,4 would output 0,1,2.
         let mut m = Machine::new(10, 0, 0);
-        m.run(&vec![5, 0, 5, 1, 5, 4]);
+        m.run(&vec![5, 0, 5, 1, 5, 4]).unwrap();
         assert_eq!(m.output, vec![0, 1, 2]);
         
         // If register A contains 2024, the program 0,1,5,4,3,0
         //   would output 4,2,5,6,7,7,7,7,3,1,0 and leave 0 in register A.
         let mut m = Machine::new(2024, 0, 0);
-        m.run(&vec![0, 1, 5, 4, 3, 0]);
+        m.run(&vec![0, 1, 5, 4, 3, 0]).unwrap();
         assert_eq!(m.output, vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]);
         assert_eq!(m.reg_a, 0);
         
         // If register B contains 29, the program 1,7 would set register B to 26.
         let mut m = Machine::new(0, 29, 0);
-        m.run(&vec![1, 7]);
+        m.run(&vec![1, 7]).unwrap();
         assert_eq!(m.reg_b, 26);
         
         // If register B contains 2024 and register C contains 43690, the program 4,0
         //  would set register B to 44354.
         let mut m = Machine::new(0, 2024, 43690);
-        m.run(&vec![4, 0]);
+        m.run(&vec![4, 0]).unwrap();
         assert_eq!(m.reg_b, 44354);
     }
 
@@ -499,8 +575,8 @@ mod tests {
              Register C: 0
              
              Program: 0,1,5,4,3,0"
-        );
-        assert_eq!(part1(&input), "4,6,3,5,6,3,5,2,1,0");
+        ).unwrap();
+        assert_eq!(part1(&input).unwrap(), "4,6,3,5,6,3,5,2,1,0");
     }
 
     #[test]
@@ -511,7 +587,7 @@ mod tests {
              Register C: 0
              
              Program: 0,3,5,4,3,0"
-        );
-        assert_eq!(part2(&input), 117440);
+        ).unwrap();
+        assert_eq!(part2(&input), Ok(117440));
     }
 }
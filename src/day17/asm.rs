@@ -0,0 +1,284 @@
+// Text format for the 3-bit programs, one instruction per line:
+//
+//   ; comments start with `;`
+//   loop:           labels name the address of the next instruction
+//       bst a       combo operands are 0-3 or the registers a, b and c,
+//                   7 is reserved but accepted so any program can be written
+//       bxl 5       literal operands are 0-7
+//       out b
+//       jnz loop    jumps take a literal or a label
+//
+// `bxc` ignores its operand, so it can be left out.
+#![allow(dead_code)]
+use std::collections::HashMap;
+use crate::parse::{self, Line, ParseError};
+use super::{ADV, BST, JNZ, BXC, OUT, BDV, CDV};
+
+const MNEMONICS: [&str; 8] = ["adv", "bxl", "bst", "jnz", "bxc", "out", "bdv", "cdv"];
+
+const EXPECTED_MNEMONIC: &str = "an instruction `adv`, `bxl`, `bst`, `jnz`, `bxc`, `out`, `bdv` or `cdv`";
+const EXPECTED_COMBO: &str = "a combo operand `0-3`, `a`, `b` or `c`";
+const EXPECTED_LITERAL: &str = "a 3-bit number";
+
+pub fn mnemonic(opcode: u32) -> Option<&'static str> {
+    MNEMONICS.get(opcode as usize).copied()
+}
+
+pub fn is_combo(opcode: u32) -> bool {
+    matches!(opcode, ADV | BST | OUT | BDV | CDV)
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// An instruction waiting for the labels to be known
+struct Pending<'a> {
+    line: Line<'a>,
+    opcode: u32,
+    operand: Option<&'a str>,
+}
+
+fn literal(line: &Line, token: &str, expected: &str) -> Result<u32, ParseError> {
+    match token.parse() {
+        Ok(value) if value <= 7 => Ok(value),
+        _ => Err(line.error(token, expected)),
+    }
+}
+
+fn operand(pending: &Pending, labels: &HashMap<&str, usize>) -> Result<u32, ParseError> {
+    let line = &pending.line;
+    let Some(token) = pending.operand else {
+        if pending.opcode == BXC {
+            return Ok(0);
+        }
+        return Err(line.error_end("an operand"));
+    };
+
+    match pending.opcode {
+        _ if is_combo(pending.opcode) => match token {
+            "a" => Ok(4),
+            "b" => Ok(5),
+            "c" => Ok(6),
+            _ => match token.parse() {
+                Ok(value) if value <= 3 || value == 7 => Ok(value),
+                _ => Err(line.error(token, EXPECTED_COMBO)),
+            },
+        },
+        JNZ if is_label(token) => {
+            let address = *labels
+                .get(token)
+                .ok_or_else(|| line.error(token, "a defined label"))?;
+            if address > 7 {
+                return Err(line.error(token, "a label at an address up to 7"));
+            }
+            Ok(address as u32)
+        }
+        JNZ => literal(line, token, "a 3-bit number or a label"),
+        _ => literal(line, token, EXPECTED_LITERAL),
+    }
+}
+
+pub fn assemble(text: &str) -> Result<Vec<u32>, ParseError> {
+    let mut labels = HashMap::new();
+    let mut pending = Vec::new();
+
+    for line in parse::lines(17, text) {
+        let code = line.text.split(';').next().unwrap();
+        let mut rest = code.trim();
+
+        // Any number of labels before the instruction
+        while let Some((name, after)) = rest.split_once(':') {
+            let name = name.trim();
+            if !is_label(name) {
+                return Err(line.error(name, "a label"));
+            }
+            if labels.insert(name, pending.len() * 2).is_some() {
+                return Err(line.error(name, "a new label"));
+            }
+            rest = after.trim();
+        }
+
+        let mut tokens = rest.split_whitespace();
+        let Some(name) = tokens.next() else {
+            continue;
+        };
+        let opcode = MNEMONICS
+            .iter()
+            .position(|m| *m == name)
+            .ok_or_else(|| line.error(name, EXPECTED_MNEMONIC))?;
+        let operand = tokens.next();
+
+        if let Some(extra) = tokens.next() {
+            return Err(line.error(extra, "end of line"));
+        }
+        pending.push(Pending { line, opcode: opcode as u32, operand });
+    }
+
+    let mut program = Vec::new();
+    for p in &pending {
+        program.push(p.opcode);
+        program.push(operand(p, &labels)?);
+    }
+    Ok(program)
+}
+
+fn format_operand(opcode: u32, operand: u32, labels: &HashMap<u32, String>) -> String {
+    if is_combo(opcode) {
+        match operand {
+            4 => String::from("a"),
+            5 => String::from("b"),
+            6 => String::from("c"),
+            7 => String::from("7 ; reserved combo operand"),
+            _ => operand.to_string(),
+        }
+    } else if let Some(label) = labels.get(&operand).filter(|_| opcode == JNZ) {
+        label.clone()
+    } else {
+        operand.to_string()
+    }
+}
+
+fn format_instruction_with(opcode: u32, operand: u32, labels: &HashMap<u32, String>) -> String {
+    let name = mnemonic(opcode).unwrap_or("???");
+    match opcode {
+        BXC if operand == 0 => String::from(name),
+        _ => format!("{name} {}", format_operand(opcode, operand, labels)),
+    }
+}
+
+pub fn format_instruction(opcode: u32, operand: u32) -> String {
+    format_instruction_with(opcode, operand, &HashMap::new())
+}
+
+// Renders any program, jumps to the start of an instruction get a label
+pub fn disassemble(program: &[u32]) -> String {
+    let mut labels = HashMap::new();
+    for pair in program.chunks_exact(2) {
+        let target = pair[1];
+        if pair[0] == JNZ && target % 2 == 0 && (target as usize) < program.len() {
+            labels.insert(target, format!("l{target}"));
+        }
+    }
+
+    let mut result = String::new();
+    for (i, pair) in program.chunks(2).enumerate() {
+        if let Some(label) = labels.get(&(i as u32 * 2)) {
+            result.push_str(&format!("{label}:\n"));
+        }
+
+        match *pair {
+            [opcode, operand] => {
+                result.push_str(&format!("    {}\n", format_instruction_with(opcode, operand, &labels)));
+            }
+            [opcode] => result.push_str(&format!("    ; opcode {opcode} without operand\n")),
+            _ => unreachable!(),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::BXL;
+    use crate::utils::Rng;
+
+    // Same shape as the puzzle inputs
+    const PROGRAM: [u32; 16] = [2, 4, 1, 5, 7, 5, 1, 6, 0, 3, 4, 2, 5, 5, 3, 0];
+
+    #[test]
+    fn test_assemble() {
+        let program = assemble(
+            "; part 2 example
+             loop:
+                 adv 3
+                 out a   ; the lowest 3 bits
+                 jnz loop"
+        ).unwrap();
+        assert_eq!(program, vec![ADV, 3, OUT, 4, JNZ, 0]);
+
+        let program = assemble("start: bst c\nbxc\nbdv b\ncdv 2\nbxl 7\njnz start").unwrap();
+        assert_eq!(program, vec![BST, 6, BXC, 0, BDV, 5, CDV, 2, BXL, 7, JNZ, 0]);
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let err = assemble("adv 3\nmov a").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 2, 1, EXPECTED_MNEMONIC, "mov"));
+
+        let err = assemble("out 4").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 1, 5, EXPECTED_COMBO, "4"));
+
+        let err = assemble("bdv 8").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 1, 5, EXPECTED_COMBO, "8"));
+
+        let err = assemble("bxl 8").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 1, 5, EXPECTED_LITERAL, "8"));
+
+        let err = assemble("  adv").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 1, 6, "an operand", "end of line"));
+
+        let err = assemble("adv 3 4").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 1, 7, "end of line", "4"));
+
+        let err = assemble("jnz end").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 1, 5, "a defined label", "end"));
+
+        let err = assemble("a:\nadv 1\na: adv 2").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 3, 1, "a new label", "a"));
+
+        let err = assemble("adv 1\nadv 1\nadv 1\nadv 1\nend: jnz end").unwrap_err();
+        assert_eq!(err, ParseError::new(17, 5, 10, "a label at an address up to 7", "end"));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        assert_eq!(
+            disassemble(&PROGRAM),
+            "l0:
+    bst a
+    bxl 5
+    cdv b
+    bxl 6
+    adv 3
+    bxc 2
+    out b
+    jnz l0
+"
+        );
+
+        // Jumps to odd addresses, reserved operands and a missing operand
+        assert_eq!(
+            disassemble(&[JNZ, 3, ADV, 7, OUT]),
+            "    jnz 3\n    adv 7 ; reserved combo operand\n    ; opcode 5 without operand\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let programs = [
+            PROGRAM.to_vec(),
+            vec![0, 1, 5, 4, 3, 0],
+            vec![0, 3, 5, 4, 3, 0],
+            vec![2, 6, 1, 7, 4, 0, 5, 0, 3, 2, 6, 1],
+            vec![JNZ, 3, ADV, 7, OUT, 7],
+        ];
+
+        for program in programs {
+            assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        let mut rng = Rng::new(17);
+        for _ in 0 .. 500 {
+            let length = 2 * (1 + rng.below(8) as usize);
+            let program: Vec<_> = (0 .. length).map(|_| rng.below(8) as u32).collect();
+            assert_eq!(assemble(&disassemble(&program)).unwrap(), program, "{program:?}");
+        }
+    }
+}
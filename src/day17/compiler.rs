@@ -0,0 +1,221 @@
+// Decodes a program once so running it many times (like the quine search
+// does) doesn't match opcodes and combo operands on every step. The literal
+// operands are folded into the ops, only register operands are read when
+// running. Every address is decoded, jumps to odd addresses land on the
+// instruction formed by the two values there, like in the Machine.
+use super::{Limits, MachineError, ADV, BDV, BST, BXC, BXL, CDV, JNZ, OUT};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Literal(u64),
+    Register(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    // Register = A >> shift
+    Shift { to: usize, shift: u32 },
+    Divide { to: usize, by: usize },
+    XorB(u64),
+    // Already reduced modulo 8
+    SetB(u64),
+    CopyB(usize),
+    Jump(usize),
+    // Jumps past the end of the program
+    Halt,
+    XorBC,
+    Out(u32),
+    OutRegister(usize),
+    Fail(MachineError),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Compiled {
+    ops: Vec<Op>,
+}
+
+fn combo(ins_p: usize, operand: u32) -> Result<Value, MachineError> {
+    match operand {
+        0 ..= 3 => Ok(Value::Literal(operand as u64)),
+        4 ..= 6 => Ok(Value::Register(operand as usize - 4)),
+        7 => Err(MachineError::ReservedOperand { ins_p }),
+        _ => Err(MachineError::InvalidCombo { ins_p, operand }),
+    }
+}
+
+fn decode(ins_p: usize, opcode: u32, operand: u32, len: usize) -> Result<Op, MachineError> {
+    let op = match opcode {
+        ADV | BDV | CDV => {
+            let to = match opcode {
+                ADV => 0,
+                BDV => 1,
+                _ => 2,
+            };
+            match combo(ins_p, operand)? {
+                Value::Literal(shift) => Op::Shift { to, shift: shift as u32 },
+                Value::Register(by) => Op::Divide { to, by },
+            }
+        }
+        BXL => Op::XorB(operand as u64),
+        BST => match combo(ins_p, operand)? {
+            Value::Literal(value) => Op::SetB(value % 8),
+            Value::Register(r) => Op::CopyB(r),
+        },
+        // Out of range targets only halt when the jump is taken
+        JNZ if operand as usize >= len => Op::Halt,
+        JNZ => Op::Jump(operand as usize),
+        BXC => Op::XorBC,
+        OUT => match combo(ins_p, operand)? {
+            Value::Literal(value) => Op::Out((value % 8) as u32),
+            Value::Register(r) => Op::OutRegister(r),
+        },
+        _ => return Err(MachineError::InvalidOpcode { ins_p, opcode }),
+    };
+    Ok(op)
+}
+
+pub fn compile(program: &[u32]) -> Result<Compiled, MachineError> {
+    if !program.len().is_multiple_of(2) {
+        return Err(MachineError::OddLength { len: program.len() });
+    }
+
+    // Decoding errors only count when the instruction runs
+    let ops = program
+        .windows(2)
+        .enumerate()
+        .map(|(ins_p, pair)| decode(ins_p, pair[0], pair[1], program.len()).unwrap_or_else(Op::Fail))
+        .collect();
+    Ok(Compiled { ops })
+}
+
+impl Compiled {
+    // Appends to `output` so a search can reuse the buffer between runs,
+    // returns the registers at the end
+    pub fn run_into(&self, registers: [u64; 3], limits: Limits, output: &mut Vec<u32>) -> Result<[u64; 3], MachineError> {
+        let mut regs = registers;
+        let start = output.len();
+        let mut ins_p = 0;
+        let mut steps = 0;
+
+        while let Some(op) = self.ops.get(ins_p) {
+            if steps == limits.steps {
+                return Err(MachineError::StepLimit(steps));
+            }
+            steps += 1;
+
+            match *op {
+                Op::Shift { to, shift } => regs[to] = regs[0] >> shift,
+                Op::Divide { to, by } => regs[to] = regs[0].checked_shr(regs[by].min(64) as u32).unwrap_or(0),
+                Op::XorB(n) => regs[1] ^= n,
+                Op::SetB(n) => regs[1] = n,
+                Op::CopyB(r) => regs[1] = regs[r] % 8,
+                Op::Jump(target) if regs[0] != 0 => {
+                    ins_p = target;
+                    continue;
+                }
+                Op::Halt if regs[0] != 0 => break,
+                Op::Jump(_) | Op::Halt => {}
+                Op::XorBC => regs[1] ^= regs[2],
+                Op::Out(n) => output.push(n),
+                Op::OutRegister(r) => output.push((regs[r] % 8) as u32),
+                Op::Fail(e) => return Err(e),
+            }
+            if output.len() - start > limits.output {
+                return Err(MachineError::OutputLimit(limits.output));
+            }
+            ins_p += 2;
+        }
+
+        Ok(regs)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::Machine;
+    use crate::utils::Rng;
+
+    // Registers and output after running a program
+    #[derive(Debug, PartialEq)]
+    struct Run {
+        registers: [u64; 3],
+        output: Vec<u32>,
+    }
+
+    fn run(compiled: &Compiled, registers: [u64; 3], limits: Limits) -> Result<Run, MachineError> {
+        let mut output = Vec::new();
+        let registers = compiled.run_into(registers, limits, &mut output)?;
+        Ok(Run { registers, output })
+    }
+
+    fn machine_run(program: &[u32], registers: [u64; 3], limits: Limits) -> Result<Run, MachineError> {
+        let mut m = Machine::new(registers[0], registers[1], registers[2]).limits(limits);
+        m.run(program)?;
+        Ok(Run { registers: [m.reg_a, m.reg_b, m.reg_c], output: m.output })
+    }
+
+    #[test]
+    fn test_compile() {
+        let compiled = compile(&[ADV, 3, BST, 2, CDV, 5, OUT, 6, JNZ, 0]).unwrap();
+        assert_eq!(compiled.ops[0], Op::Shift { to: 0, shift: 3 });
+        assert_eq!(compiled.ops[2], Op::SetB(2));
+        assert_eq!(compiled.ops[4], Op::Divide { to: 2, by: 1 });
+        assert_eq!(compiled.ops[6], Op::OutRegister(2));
+        assert_eq!(compiled.ops[8], Op::Jump(0));
+
+        let compiled = compile(&[BST, 6, BXL, 5, OUT, 3, JNZ, 6]).unwrap();
+        assert_eq!(compiled.ops[0], Op::CopyB(2));
+        assert_eq!(compiled.ops[2], Op::XorB(5));
+        assert_eq!(compiled.ops[4], Op::Out(3));
+        assert_eq!(compiled.ops[6], Op::Jump(6));
+        assert_eq!(compile(&[OUT, 4, JNZ, 7]).unwrap().ops[2], Op::Halt);
+
+        assert_eq!(compile(&[ADV]), Err(MachineError::OddLength { len: 1 }));
+    }
+
+    #[test]
+    fn test_run() {
+        let compiled = compile(&[0, 1, 5, 4, 3, 0]).unwrap();
+        let run = run(&compiled, [729, 0, 0], Limits::NONE).unwrap();
+        assert_eq!(run.output, vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+        assert_eq!(run.registers, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_same_as_machine() {
+        let mut rng = Rng::new(17);
+        let limits = Limits { steps: 500, output: 50 };
+
+        for _ in 0 .. 5000 {
+            let len = 2 * (1 + rng.below(8) as usize);
+            let program: Vec<u32> = (0 .. len).map(|_| rng.below(8) as u32).collect();
+            let registers = [rng.below(1 << 40), rng.below(1 << 10), rng.below(1 << 10)];
+
+            let expected = machine_run(&program, registers, limits);
+            let found = run(&compile(&program).unwrap(), registers, limits);
+            assert_eq!(found, expected, "program {program:?} with {registers:?}");
+        }
+    }
+
+    // The step the quine search repeats, one iteration of the loop body for
+    // every candidate A. Its speed is tracked by `aoc-2024 bench` on day 17
+    // part 2.
+    #[test]
+    fn test_search_same_as_machine() {
+        // Loop body of a puzzle input, without the final `jnz 0`
+        let body = [2, 4, 1, 5, 7, 5, 1, 6, 0, 3, 4, 2, 5, 5];
+        let compiled = compile(&body).unwrap();
+        let mut output = Vec::new();
+
+        for a in 0 .. 100_000 {
+            let mut m = Machine::new(a, 0, 0);
+            m.run(&body).unwrap();
+
+            output.clear();
+            compiled.run_into([a, 0, 0], Limits::NONE, &mut output).unwrap();
+            assert_eq!(output, m.output, "A = {a}");
+        }
+    }
+}
@@ -0,0 +1,426 @@
+#![allow(dead_code)]
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use super::{asm, Machine, MachineError, OUT};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Register {
+    A,
+    B,
+    C,
+}
+
+use Register::*;
+
+const REGISTERS: [Register; 3] = [A, B, C];
+
+impl Register {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "a" => Some(A),
+            "b" => Some(B),
+            "c" => Some(C),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> char {
+        match self {
+            A => 'a',
+            B => 'b',
+            C => 'c',
+        }
+    }
+}
+
+fn registers(m: &Machine) -> [u64; 3] {
+    [m.reg_a, m.reg_b, m.reg_c]
+}
+
+// An executed instruction with the registers around it
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceEntry {
+    pub ins_p: usize,
+    pub opcode: u32,
+    pub operand: u32,
+    pub before: [u64; 3],
+    pub after: [u64; 3],
+    pub output: Option<u32>,
+}
+
+impl TraceEntry {
+    pub fn changes(&self) -> Vec<(Register, u64, u64)> {
+        REGISTERS
+            .iter()
+            .zip(self.before.iter().zip(self.after))
+            .filter(|(_, (before, after))| *before != after)
+            .map(|(r, (before, after))| (*r, *before, after))
+            .collect()
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:3}: {}", self.ins_p, asm::format_instruction(self.opcode, self.operand))?;
+        for (r, before, after) in self.changes() {
+            write!(f, ", {}: {before} -> {after}", r.name())?;
+        }
+        if let Some(value) = self.output {
+            write!(f, ", out {value}")?;
+        }
+        Ok(())
+    }
+}
+
+// Why `resume` gave back the control
+#[derive(Debug, PartialEq)]
+pub enum Stop {
+    Halted,
+    Breakpoint(usize),
+    Output(u32),
+    Watch { register: Register, before: u64, after: u64 },
+    Error(MachineError),
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Halted => write!(f, "halted"),
+            Stop::Breakpoint(ins_p) => write!(f, "breakpoint at {ins_p}"),
+            Stop::Output(value) => write!(f, "output {value}"),
+            Stop::Watch { register, before, after } => write!(f, "watch {}: {before} -> {after}", register.name()),
+            Stop::Error(e) => write!(f, "error: {e}"),
+        }
+    }
+}
+
+// Runs a program on a machine one instruction at a time, recording each
+// one in the trace
+pub struct Debugger {
+    pub machine: Machine,
+    program: Vec<u32>,
+    breakpoints: HashSet<usize>,
+    break_on_out: bool,
+    watches: HashSet<Register>,
+    trace: Vec<TraceEntry>,
+}
+
+impl Debugger {
+    pub fn new(machine: Machine, program: Vec<u32>) -> Self {
+        Self {
+            machine,
+            program,
+            breakpoints: HashSet::new(),
+            break_on_out: false,
+            watches: HashSet::new(),
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn program(&self) -> &[u32] {
+        &self.program
+    }
+
+    // A trailing opcode without operand also stops the machine
+    pub fn is_halted(&self) -> bool {
+        self.machine.ins_p + 1 >= self.program.len()
+    }
+
+    pub fn add_breakpoint(&mut self, ins_p: usize) {
+        self.breakpoints.insert(ins_p);
+    }
+
+    pub fn remove_breakpoint(&mut self, ins_p: usize) -> bool {
+        self.breakpoints.remove(&ins_p)
+    }
+
+    pub fn break_on_out(&mut self, enabled: bool) {
+        self.break_on_out = enabled;
+    }
+
+    pub fn watch(&mut self, register: Register) {
+        self.watches.insert(register);
+    }
+
+    pub fn unwatch(&mut self, register: Register) -> bool {
+        self.watches.remove(&register)
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        &self.trace
+    }
+
+    // Runs the next instruction, None once the machine halted. Failed
+    // instructions leave the machine as it was.
+    pub fn step(&mut self) -> Result<Option<&TraceEntry>, MachineError> {
+        if self.is_halted() {
+            self.machine.halt = true;
+            return Ok(None);
+        }
+
+        let ins_p = self.machine.ins_p;
+        let (opcode, operand) = (self.program[ins_p], self.program[ins_p + 1]);
+        let before = registers(&self.machine);
+        let outputs = self.machine.output.len();
+
+        self.machine.run_inst(opcode, operand)?;
+
+        self.trace.push(TraceEntry {
+            ins_p,
+            opcode,
+            operand,
+            before,
+            after: registers(&self.machine),
+            output: self.machine.output.get(outputs).copied(),
+        });
+        Ok(self.trace.last())
+    }
+
+    // Runs at least one instruction and then until something stops it
+    pub fn resume(&mut self) -> Stop {
+        loop {
+            let entry = match self.step() {
+                Ok(Some(entry)) => entry.clone(),
+                Ok(None) => return Stop::Halted,
+                Err(e) => return Stop::Error(e),
+            };
+
+            if let Some(value) = entry.output.filter(|_| self.break_on_out && entry.opcode == OUT) {
+                return Stop::Output(value);
+            }
+
+            let watched = entry
+                .changes()
+                .into_iter()
+                .find(|(r, _, _)| self.watches.contains(r));
+            if let Some((register, before, after)) = watched {
+                return Stop::Watch { register, before, after };
+            }
+
+            if self.is_halted() {
+                self.machine.halt = true;
+                return Stop::Halted;
+            }
+            if self.breakpoints.contains(&self.machine.ins_p) {
+                return Stop::Breakpoint(self.machine.ins_p);
+            }
+        }
+    }
+
+    fn listing(&self) -> String {
+        let mut result = String::new();
+        for (i, pair) in self.program.chunks_exact(2).enumerate() {
+            let ins_p = i * 2;
+            let current = if ins_p == self.machine.ins_p { "=>" } else { "  " };
+            let breakpoint = if self.breakpoints.contains(&ins_p) { '*' } else { ' ' };
+            result.push_str(&format!("{current}{breakpoint}{ins_p:3}: {}\n",
+                                     asm::format_instruction(pair[0], pair[1])));
+        }
+        result
+    }
+
+    fn status(&self) -> String {
+        let output: Vec<_> = self.machine.output.iter().map(|n| n.to_string()).collect();
+        format!("a={} b={} c={} ip={} output={}",
+                self.machine.reg_a, self.machine.reg_b, self.machine.reg_c,
+                self.machine.ins_p, output.join(","))
+    }
+}
+
+const HELP: &str = "\
+s, step [n]          run the next n instructions
+c, continue          run until a breakpoint, a watch or the end
+b, break <ip>|out    stop before the instruction at ip, or after each out
+d, delete <ip>|out   remove a breakpoint
+w, watch <a|b|c>     stop when the register changes
+unwatch <a|b|c>      remove a watch
+r, regs              show the registers and the output
+l, list              show the program
+t, trace [n]         show the last n executed instructions
+q, quit              leave the debugger
+An empty line repeats the last command.";
+
+fn count(arg: Option<&str>, default: usize) -> Result<usize, String> {
+    match arg {
+        None => Ok(default),
+        Some(n) => n.parse().map_err(|_| format!("expected a count, found `{n}`")),
+    }
+}
+
+fn address(arg: &str) -> Result<usize, String> {
+    arg.parse().map_err(|_| format!("expected an address, found `{arg}`"))
+}
+
+fn register(arg: &str) -> Result<Register, String> {
+    Register::parse(arg).ok_or_else(|| format!("expected a register, found `{arg}`"))
+}
+
+// Runs a command returning what it prints
+fn command(debugger: &mut Debugger, name: &str, arg: Option<&str>) -> Result<String, String> {
+    let mut result = String::new();
+
+    match (name, arg) {
+        ("s" | "step", _) => {
+            for _ in 0 .. count(arg, 1)? {
+                match debugger.step() {
+                    Ok(Some(entry)) => result.push_str(&format!("{entry}\n")),
+                    Ok(None) => {
+                        result.push_str(&format!("{}\n", Stop::Halted));
+                        break;
+                    }
+                    Err(e) => {
+                        result.push_str(&format!("{}\n", Stop::Error(e)));
+                        break;
+                    }
+                }
+            }
+        }
+        ("c" | "continue", None) => result = format!("{}\n", debugger.resume()),
+        ("b" | "break", Some("out")) => debugger.break_on_out(true),
+        ("d" | "delete", Some("out")) => debugger.break_on_out(false),
+        ("b" | "break", Some(arg)) => debugger.add_breakpoint(address(arg)?),
+        ("d" | "delete", Some(arg)) => {
+            let ins_p = address(arg)?;
+            if !debugger.remove_breakpoint(ins_p) {
+                return Err(format!("no breakpoint at {ins_p}"));
+            }
+        }
+        ("w" | "watch", Some(arg)) => debugger.watch(register(arg)?),
+        ("unwatch", Some(arg)) => {
+            if !debugger.unwatch(register(arg)?) {
+                return Err(format!("{arg} isn't watched"));
+            }
+        }
+        ("r" | "regs", None) => result = format!("{}\n", debugger.status()),
+        ("l" | "list", None) => result = debugger.listing(),
+        ("t" | "trace", _) => {
+            let trace = debugger.trace();
+            for entry in &trace[trace.len().saturating_sub(count(arg, 10)?) ..] {
+                result.push_str(&format!("{entry}\n"));
+            }
+        }
+        ("h" | "help", None) => result = format!("{HELP}\n"),
+        ("q" | "quit", None) => {}
+        _ => {
+            let line = [Some(name), arg].iter().flatten().copied().collect::<Vec<_>>().join(" ");
+            return Err(format!("unknown command `{line}`, try `help`"));
+        }
+    }
+    Ok(result)
+}
+
+// Reads commands from `input` until `quit` or the end of the input
+pub fn repl(debugger: &mut Debugger, input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+    let mut last = String::new();
+
+    write!(out, "> ")?;
+    out.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            last = line;
+        }
+
+        let mut words = last.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let arg = words.next();
+        if matches!(name, "q" | "quit") {
+            break;
+        }
+
+        if !name.is_empty() {
+            match command(debugger, name, arg) {
+                Ok(text) => write!(out, "{text}")?,
+                Err(e) => writeln!(out, "{e}")?,
+            }
+        }
+        write!(out, "> ")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs A in base 8, lowest digit first
+    fn debugger(reg_a: u64) -> Debugger {
+        let program = asm::assemble("loop: out a\nadv 3\njnz loop").unwrap();
+        Debugger::new(Machine::new(reg_a, 0, 0), program)
+    }
+
+    #[test]
+    fn test_step() {
+        let mut d = debugger(10);
+
+        let entry = d.step().unwrap().unwrap().clone();
+        assert_eq!(entry.output, Some(2));
+        assert_eq!(entry.to_string(), "  0: out a, out 2");
+
+        let entry = d.step().unwrap().unwrap().clone();
+        assert_eq!(entry.changes(), vec![(A, 10, 1)]);
+        assert_eq!(entry.to_string(), "  2: adv 3, a: 10 -> 1");
+
+        while d.step().unwrap().is_some() {}
+        assert!(d.machine.halt);
+        assert_eq!(d.machine.output, vec![2, 1]);
+        assert_eq!(d.trace().len(), 6);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut d = debugger(0o753);
+        d.add_breakpoint(4);
+        assert_eq!(d.resume(), Stop::Breakpoint(4));
+        assert_eq!(d.resume(), Stop::Breakpoint(4));
+        assert!(d.remove_breakpoint(4));
+
+        d.break_on_out(true);
+        assert_eq!(d.resume(), Stop::Output(7));
+        d.break_on_out(false);
+        assert_eq!(d.resume(), Stop::Halted);
+        assert_eq!(d.machine.output, vec![3, 5, 7]);
+    }
+
+    #[test]
+    fn test_watch() {
+        let mut d = debugger(0o75);
+        d.watch(A);
+        assert_eq!(d.resume(), Stop::Watch { register: A, before: 0o75, after: 0o7 });
+        assert_eq!(d.resume(), Stop::Watch { register: A, before: 0o7, after: 0 });
+        assert_eq!(d.resume(), Stop::Halted);
+    }
+
+    #[test]
+    fn test_error() {
+        let mut d = Debugger::new(Machine::new(1, 0, 0), vec![OUT, 4, OUT, 7]);
+        assert_eq!(d.resume(), Stop::Error(MachineError::ReservedOperand { ins_p: 2 }));
+        assert_eq!(d.machine.ins_p, 2);
+        assert_eq!(d.trace().len(), 1);
+    }
+
+    #[test]
+    fn test_repl() {
+        let mut d = debugger(10);
+        let script = "break 4\ncontinue\nregs\nstep\n\nlist\nbreak x\nfoo\ntrace 2\nquit\nregs\n";
+        let mut out = Vec::new();
+        repl(&mut d, script.as_bytes(), &mut out).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+> > breakpoint at 4
+> a=1 b=0 c=0 ip=4 output=2
+>   4: jnz 0
+>   0: out a, out 1
+>      0: out a
+=>   2: adv 3
+  *  4: jnz 0
+> expected an address, found `x`
+> unknown command `foo`, try `help`
+>   4: jnz 0
+  0: out a, out 1
+> ");
+    }
+}
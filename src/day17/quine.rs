@@ -0,0 +1,219 @@
+// Finds the lowest A that makes a program print itself. It works for the
+// programs shaped like the puzzle inputs:
+//
+//   - the last instruction is `jnz 0` and there are no other jumps
+//   - each iteration shifts A by 3 with a single `adv 3`
+//   - each iteration prints a single value
+//   - B and C are set from A before being read, so an iteration only
+//     depends on the value of A when it starts
+//
+// Then the last value printed depends only on the highest 3 bits of A, the
+// one before on the next 3 bits and the ones already chosen, and so on. The
+// search goes backwards from the last value adding 3 bits at a time.
+use std::error::Error;
+use std::fmt;
+use super::compiler::{compile, Compiled};
+use super::{Limits, Machine, MachineError, ADV, BDV, BST, BXC, BXL, CDV, JNZ, OUT};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum QuineError {
+    OddLength,
+    NoFinalJump,
+    InnerJump { ins_p: usize },
+    Shifts { count: usize },
+    ShiftAmount { ins_p: usize },
+    Outputs { count: usize },
+    CarriedRegister { register: char, ins_p: usize },
+    ReservedOperand { ins_p: usize },
+    Machine(MachineError),
+    NoSolution,
+}
+
+impl fmt::Display for QuineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuineError::OddLength => write!(f, "the last opcode of the program has no operand"),
+            QuineError::NoFinalJump => write!(f, "the program must end with `jnz 0` to loop until A is 0"),
+            QuineError::InnerJump { ins_p } => {
+                write!(f, "jump at {ins_p} inside the loop, only the final `jnz 0` is supported")
+            }
+            QuineError::Shifts { count } => {
+                write!(f, "A must be shifted by 3 once per iteration, found {count} `adv` instructions")
+            }
+            QuineError::ShiftAmount { ins_p } => {
+                write!(f, "A must be shifted by 3 once per iteration, the `adv` at {ins_p} isn't `adv 3`")
+            }
+            QuineError::Outputs { count } => {
+                write!(f, "the loop must print one value per iteration, found {count} `out` instructions")
+            }
+            QuineError::CarriedRegister { register, ins_p } => write!(
+                f,
+                "register {register} is read at {ins_p} before being set in the loop, \
+                 so each iteration depends on the previous one"
+            ),
+            QuineError::ReservedOperand { ins_p } => write!(f, "reserved combo operand 7 at {ins_p}"),
+            QuineError::Machine(e) => write!(f, "{e}"),
+            QuineError::NoSolution => write!(f, "no value of A makes the program print itself"),
+        }
+    }
+}
+
+impl Error for QuineError {}
+
+impl From<MachineError> for QuineError {
+    fn from(e: MachineError) -> Self {
+        QuineError::Machine(e)
+    }
+}
+
+// Registers a combo operand reads: 0 for A, 1 for B and 2 for C
+fn combo_register(operand: u32) -> Option<usize> {
+    match operand {
+        4 ..= 6 => Some(operand as usize - 4),
+        _ => None,
+    }
+}
+
+// Checks the program has the supported shape, returns the loop body
+pub fn analyse(program: &[u32]) -> Result<&[u32], QuineError> {
+    if !program.len().is_multiple_of(2) {
+        return Err(QuineError::OddLength);
+    }
+    if program.len() < 2 || program[program.len() - 2 ..] != [JNZ, 0] {
+        return Err(QuineError::NoFinalJump);
+    }
+
+    let body = &program[.. program.len() - 2];
+    let mut shifts = 0;
+    let mut outputs = 0;
+    // B and C, A always comes from the previous iteration
+    let mut set = [true, false, false];
+
+    for (i, pair) in body.chunks(2).enumerate() {
+        let ins_p = i * 2;
+        let (opcode, operand) = (pair[0], pair[1]);
+
+        let combo = opcode != BXL && opcode != JNZ && opcode != BXC;
+        if combo && operand == 7 {
+            return Err(QuineError::ReservedOperand { ins_p });
+        }
+
+        let mut reads = Vec::new();
+        if combo {
+            reads.extend(combo_register(operand));
+        }
+        match opcode {
+            BXL => reads.push(1),
+            BXC => reads.extend([1, 2]),
+            _ => {}
+        }
+        if let Some(register) = reads.into_iter().find(|r| !set[*r]) {
+            let register = ['a', 'b', 'c'][register];
+            return Err(QuineError::CarriedRegister { register, ins_p });
+        }
+
+        match opcode {
+            ADV if operand == 3 => shifts += 1,
+            ADV => return Err(QuineError::ShiftAmount { ins_p }),
+            JNZ => return Err(QuineError::InnerJump { ins_p }),
+            OUT => outputs += 1,
+            BXL | BST | BXC | BDV => set[1] = true,
+            CDV => set[2] = true,
+            _ => {}
+        }
+    }
+
+    if shifts != 1 {
+        return Err(QuineError::Shifts { count: shifts });
+    }
+    if outputs != 1 {
+        return Err(QuineError::Outputs { count: outputs });
+    }
+    Ok(body)
+}
+
+// Value printed by a single iteration starting with `reg_a`, `output` is
+// only a buffer reused between calls
+fn iteration(body: &Compiled, reg_a: u64, output: &mut Vec<u32>) -> Option<u32> {
+    output.clear();
+    body.run_into([reg_a, 0, 0], Limits::NONE, output).ok()?;
+    output.first().copied()
+}
+
+pub fn solve(program: &[u32]) -> Result<u64, QuineError> {
+    let body = analyse(program)?;
+    let body = compile(body)?;
+    let mut candidates = vec![0u64];
+    let mut output = Vec::new();
+
+    for target in program.iter().rev() {
+        candidates = candidates
+            .iter()
+            .flat_map(|a| (0 .. 8).map(move |bits| (a << 3) | bits))
+            .filter(|a| iteration(&body, *a, &mut output) == Some(*target))
+            .collect();
+    }
+
+    // The highest bits can be 0, those stop one iteration too early
+    candidates.sort();
+    let limits = Limits { steps: 1000 * program.len(), output: program.len() };
+    candidates
+        .into_iter()
+        .find(|a| {
+            let mut m = Machine::new(*a, 0, 0).limits(limits);
+            m.run(program).is_ok() && m.output == program
+        })
+        .ok_or(QuineError::NoSolution)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day17::asm::assemble;
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(solve(&[0, 3, 5, 4, 3, 0]), Ok(117440));
+
+        // Same shape as the puzzle inputs
+        let program = [2, 4, 1, 5, 7, 5, 1, 6, 0, 3, 4, 2, 5, 5, 3, 0];
+        let a = solve(&program).unwrap();
+        let mut m = Machine::new(a, 0, 0);
+        m.run(&program).unwrap();
+        assert_eq!(m.output, program);
+    }
+
+    #[test]
+    fn test_analyse_errors() {
+        let cases = [
+            ("adv 3\nout a", QuineError::NoFinalJump),
+            ("adv 3\njnz 0\nout a\njnz 0", QuineError::InnerJump { ins_p: 2 }),
+            ("out a\njnz 0", QuineError::Shifts { count: 0 }),
+            ("adv 1\nout a\njnz 0", QuineError::ShiftAmount { ins_p: 0 }),
+            ("adv 3\nout a\nout b\njnz 0", QuineError::CarriedRegister { register: 'b', ins_p: 4 }),
+            ("adv 3\nbst a\nout a\nout b\njnz 0", QuineError::Outputs { count: 2 }),
+            ("bxc\nadv 3\nout b\njnz 0", QuineError::CarriedRegister { register: 'b', ins_p: 0 }),
+        ];
+
+        for (text, err) in cases {
+            assert_eq!(analyse(&assemble(text).unwrap()), Err(err));
+        }
+        assert_eq!(analyse(&[ADV, 7, JNZ, 0]), Err(QuineError::ReservedOperand { ins_p: 0 }));
+        assert_eq!(
+            QuineError::Outputs { count: 2 }.to_string(),
+            "the loop must print one value per iteration, found 2 `out` instructions"
+        );
+        assert_eq!(
+            QuineError::from(MachineError::OddLength { len: 3 }).to_string(),
+            "the program has 3 values, the last opcode has no operand"
+        );
+    }
+
+    #[test]
+    fn test_no_solution() {
+        // Always prints 0, can't print the 3 of `adv 3`
+        let program = assemble("adv 3\nbst 0\nout b\njnz 0").unwrap();
+        assert_eq!(solve(&program), Err(QuineError::NoSolution));
+    }
+}
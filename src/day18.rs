@@ -1,28 +1,48 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
-use std::collections::{HashMap,HashSet};
-use colored::Colorize;
+use std::collections::HashSet;
+use crate::utils::Grid;
+use crate::viz::{self, GridFrame, Style};
+use crate::parse::{self, ParseError};
+use crate::search;
 
 type Input = Vec<(usize, usize)>;
 
+// Size of the memory space and bytes fallen for the first part. The path
+// goes from the top left corner to the bottom right one.
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    width: usize,
+    height: usize,
+    bytes: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { width: 71, height: 71, bytes: 1024 }
+    }
+}
+
+impl Config {
+    fn end(&self) -> (usize, usize) {
+        (self.width - 1, self.height - 1)
+    }
+}
+
 #[aoc_generator(day18)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| {
-            let points: Vec<usize> = l.trim().split(",").map(|e| e.parse().unwrap()).collect();
-            (points[0], points[1])
+fn parse(input: &str) -> Result<Input, ParseError> {
+    parse::lines(18, input)
+        .map(|line| {
+            let points = line.split_n(",", 2, "`<x>,<y>`")?;
+            let x = line.parse(points[0], "a coordinate")?;
+            let y = line.parse(points[1], "a coordinate")?;
+            Ok((x, y))
         })
         .collect()
 }
 
-fn gen_map(width: usize, height: usize, input: &Input, bytes: usize) -> Vec<Vec<char>> {
-    let mut map = Vec::<Vec<char>>::with_capacity(height);
-    for i in 0 .. height {
-        let mut v = Vec::<char>::with_capacity(width);
-        v.resize(width, '.');
-        map.push(v);
-    }
+fn gen_map(width: usize, height: usize, input: &Input, bytes: usize) -> Grid<char> {
+    let mut map = Grid::new(width, height, '.');
 
     for i in 0 .. bytes {
         let (x, y) = input[i];
@@ -31,29 +51,27 @@ fn gen_map(width: usize, height: usize, input: &Input, bytes: usize) -> Vec<Vec<
     map
 }
 
-fn print_map(map: &Vec<Vec<char>>) {
-    for y in 0 .. map.len() {
-        for x in 0 .. map[y].len() {
-            print!("{}", map[y][x]);
-        }
-        println!();
+fn map_cell(c: &char) -> (char, Style) {
+    match c {
+        '#' => ('#', Style::Wall),
+        c => (*c, Style::Plain),
     }
 }
 
-fn print_path(map: &Vec<Vec<char>>, path: &Vec<(usize, usize)>) {
+fn print_map(map: &Grid<char>) {
+    viz::print(&GridFrame::new(map, |_, c| map_cell(c)));
+}
+
+fn print_path(map: &Grid<char>, path: &Vec<(usize, usize)>) {
     let points = HashSet::<&(usize, usize)>::from_iter(path);
 
-    for y in 0 .. map.len() {
-        for x in 0 .. map[y].len() {
-            if points.contains(&(x, y)) {
-                print!("{}", "O".red());
-            } else {
-                print!("{}", map[y][x]);
-            }
-            
+    viz::print(&GridFrame::new(map, |(y, x), c| {
+        if points.contains(&(x, y)) {
+            ('O', Style::Path)
+        } else {
+            map_cell(c)
         }
-        println!();
-    }
+    }));
 }
 
 fn h(end: (usize, usize)) -> impl Fn((usize, usize)) -> u32 {
@@ -68,92 +86,40 @@ fn h(end: (usize, usize)) -> impl Fn((usize, usize)) -> u32 {
     }
 }
 
-fn min(open: &HashSet::<(usize, usize)>, fscore: &HashMap::<(usize, usize), u32>) -> (usize, usize) {
-    *open
-        .iter()
-        .min_by(|p1, p2| {
-            let s1 = fscore.get(p1).unwrap_or(&u32::MAX);
-            let s2 = fscore.get(p2).unwrap_or(&u32::MAX);
-            s1.cmp(s2)
-        })
-        .unwrap()
-}
-
-fn neighbours(map: &Vec<Vec<char>>, (x, y): (usize, usize)) -> Vec<((usize, usize), u32)> {
+fn neighbours(map: &Grid<char>, (x, y): (usize, usize)) -> Vec<((usize, usize), u32)> {
     let mut result = Vec::new();
     if x > 0 && map[y][x-1] == '.' {
         result.push(((x - 1, y), 1));
     }
-    if x < map[y].len() - 1 && map[y][x+1] == '.' {
+    if x < map.width() - 1 && map[y][x+1] == '.' {
         result.push(((x + 1, y), 1));
     }
     if y > 0 && map[y-1][x] == '.' {
         result.push(((x, y - 1), 1));
     }
-    if y < map.len() - 1 && map[y+1][x] == '.' {
+    if y < map.height() - 1 && map[y+1][x] == '.' {
         result.push(((x, y + 1), 1));
     }
 
     result
 }
 
-fn build_path(from_node: &HashMap::<(usize, usize), (usize, usize)>, last: &(usize, usize)) -> Vec<(usize, usize)> {
-    let mut result = Vec::from([*last]);
-    let mut current = last;
-
-    while let Some(val) = from_node.get(current) {
-        result.push(*val);
-        current = val
-    }
-    result.reverse();
-    result
-}
-
 // Implements A* algorithm for the problem
-fn search_path(map: &Vec<Vec<char>>, start: (usize, usize), end: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+fn search_path(map: &Grid<char>, start: (usize, usize), end: (usize, usize)) -> Option<Vec<(usize, usize)>> {
     let h = h(end);
 
-    // TODO: Improve performance with binary heap. But how to update the values with its fscore?
-    let mut open = HashSet::<(usize, usize)>::from([start]);
-    let mut from_node = HashMap::<(usize, usize), (usize, usize)>::new();
-
-    let mut gscore = HashMap::<(usize, usize), u32>::new();
-    gscore.insert(start, 0);
-
-    let mut fscore = HashMap::<(usize, usize), u32>::new();
-    fscore.insert(start, h(start));
-
-    while !open.is_empty() {
-        let current = min(&open, &fscore);
-
-        if current.0 == end.0 && current.1 == end.1 {
-            return Some(build_path(&from_node, &current));
-        }
-
-        open.remove(&current);
-
-        let current_score = *gscore.get(&current).unwrap();
-        let neighbours = neighbours(map, current);
-
-        for (neighbour, score) in neighbours {
-            let tentative_score = current_score + score;
-            
-            if tentative_score < *gscore.get(&neighbour).unwrap_or(&u32::MAX) {
-                from_node.insert(neighbour, current);
-                gscore.insert(neighbour, tentative_score);
-                fscore.insert(neighbour, tentative_score + h(neighbour));
-                if !open.contains(&neighbour) {
-                    open.insert(neighbour);
-                }
-            }
-        }
-    }
-    None
+    search::astar(start, |&p| neighbours(map, p), |&p| h(p), |&p| p == end)
+        .map(|(path, _)| path)
 }
+
 #[aoc(day18, part1)]
 fn part1(input: &Input) -> usize {
-    let map = gen_map(71, 71, &input, 1024);
-    let path = search_path(&map, (0, 0), (70, 70)).unwrap();
+    part1_with(input, &Config::default())
+}
+
+fn part1_with(input: &Input, config: &Config) -> usize {
+    let map = gen_map(config.width, config.height, &input, config.bytes);
+    let path = search_path(&map, (0, 0), config.end()).unwrap();
     // print_path(&map, &path);
     path.len() - 1
 }
@@ -185,7 +151,11 @@ fn block_path(width: usize, height: usize, start: (usize, usize), end: (usize, u
 
 #[aoc(day18, part2)]
 fn part2(input: &Input) -> String {
-    let r = block_path(71, 71, (0,0), (70,70), &input);
+    part2_with(input, &Config::default())
+}
+
+fn part2_with(input: &Input, config: &Config) -> String {
+    let r = block_path(config.width, config.height, (0,0), config.end(), &input);
     format!("{},{}", r.0, r.1)
 }
 
@@ -194,12 +164,14 @@ fn part2(input: &Input) -> String {
 mod tests {
     use super::*;
 
+    const EXAMPLE: Config = Config { width: 7, height: 7, bytes: 12 };
+
     #[test]
     fn test_parse() {
         let input = parse(
             "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1
              1,2\n5,5\n2,5\n6,5\n1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n2,0"
-        );
+        ).unwrap();
         assert_eq!(input.len(), 25);
         assert_eq!(input[0].0, 5);
         assert_eq!(input[0].1, 4);
@@ -212,7 +184,7 @@ mod tests {
         let input = parse(
             "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1
              1,2\n5,5\n2,5\n6,5\n1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n2,0"
-        );
+        ).unwrap();
 
         let map = gen_map(7, 7, &input, 12);
         print_map(&map);
@@ -228,7 +200,7 @@ mod tests {
         let input = parse(
             "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1
              1,2\n5,5\n2,5\n6,5\n1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n2,0"
-        );
+        ).unwrap();
 
         let map = gen_map(7, 7, &input, 12);
         let path = search_path(&map, (0, 0), (6, 6)).unwrap();
@@ -241,9 +213,20 @@ mod tests {
         let input = parse(
             "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1
              1,2\n5,5\n2,5\n6,5\n1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n2,0"
-        );
+        ).unwrap();
 
         let block = block_path(7, 7, (0,0), (6, 6), &input);
         assert_eq!(block, (6, 1));
     }
+
+    #[test]
+    fn test_example() {
+        let input = parse(
+            "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1
+             1,2\n5,5\n2,5\n6,5\n1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n2,0"
+        ).unwrap();
+
+        assert_eq!(part1_with(&input, &EXAMPLE), 22);
+        assert_eq!(part2_with(&input, &EXAMPLE), "6,1");
+    }
 }
@@ -1,30 +1,53 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
 use std::collections::{HashSet, HashMap};
+use crate::parse::{self, Line, ParseError};
 
 type Input = (Vec<String>, Vec<String>);
 
+fn is_color(c: char) -> bool {
+    matches!(c, 'w' | 'u' | 'b' | 'r' | 'g')
+}
+
+// Checks the token only uses stripe colors
+fn color_error(line: &Line, token: &str) -> Option<ParseError> {
+    token
+        .char_indices()
+        .find(|(_, c)| !is_color(*c))
+        .map(|(i, _)| line.error(&token[i .. i + 1], "one of `wubrg`"))
+}
+
 #[aoc_generator(day19)]
-fn parse(input: &str) -> Input {
-    let mut lines = input.lines();
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let mut lines = parse::lines(19, input);
 
     let mut patterns = Vec::<String>::new();
     let mut out = Vec::<String>::new();
 
-    let fline = lines.next().unwrap();
+    let fline = lines.next().ok_or_else(|| ParseError::eof(19, input, "the towel patterns"))?;
 
     for elem in fline.trim().split(", ") {
+        if let Some(err) = color_error(&fline, elem) {
+            return Err(err);
+        }
         patterns.push(String::from(elem));
     }
 
     // Skip line
-    lines.next();
+    if let Some(line) = lines.next() {
+        if !line.is_empty() {
+            return Err(line.error(line.trim(), "a blank line"));
+        }
+    }
 
-    while let Some(l) = lines.next() {
-        out.push(l.trim().to_string());
+    for line in lines {
+        if let Some(err) = color_error(&line, line.trim()) {
+            return Err(err);
+        }
+        out.push(line.trim().to_string());
     }
 
-    (patterns, out)
+    Ok((patterns, out))
 }
 
 fn has_pattern(patterns: &HashSet<String>, line: &String, start: usize, end: usize) -> bool {
@@ -190,7 +213,7 @@ mod tests {
              bwurrg
              brgr
              bbrgwb"
-        );
+        ).unwrap();
         assert_eq!(patterns, vec!["r", "wr", "b", "g", "bwu", "rb", "gb", "br"]);
         assert_eq!(lines, vec![
             "brwrr",
@@ -243,7 +266,7 @@ mod tests {
              bwurrg
              brgr
              bbrgwb"
-        );
+        ).unwrap();
         assert_eq!(part1(&input), 6);
     }
 
@@ -275,7 +298,7 @@ mod tests {
              bwurrg
              brgr
              bbrgwb"
-        );
+        ).unwrap();
         assert_eq!(part2(&input), 16);
     }
 }
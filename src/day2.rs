@@ -1,23 +1,22 @@
 #![allow(dead_code, unused_variables, unused_mut)]
 
+use crate::parse::{self, ParseError};
+
 type Input = Vec<Vec<u32>>;
 
 #[aoc_generator(day2)]
-fn parse(input: &str) -> Input {
+fn parse(input: &str) -> Result<Input, ParseError> {
     let mut result = Vec::new();
-    input
-        .lines()
-        .for_each(|l| {
-            let mut entry = Vec::new();
-            for elem in l.trim().split_whitespace() {
-                if let Ok(value) = elem.parse() {
-                    entry.push(value);
-                }
-            }
-            result.push(entry);
-        });
-    
-    result
+
+    for line in parse::lines(2, input) {
+        let mut entry = Vec::new();
+        for elem in line.trim().split_whitespace() {
+            entry.push(line.parse(elem, "a level number")?);
+        }
+        result.push(entry);
+    }
+
+    Ok(result)
 }
 
 #[derive(Debug, PartialEq)]
@@ -28,8 +27,10 @@ enum Check {
 
 fn check_safety(entry: &Vec<u32>) -> Check {
     let mut entries = entry.iter();
-    let mut first = entries.next().unwrap();
-    let mut last = entries.next().unwrap();
+    // Nothing can change too much with less than two levels
+    let (Some(mut first), Some(mut last)) = (entries.next(), entries.next()) else {
+        return Check::Safe;
+    };
 
     if first == last || first.abs_diff(*last) > 3 {
         return Check::Unsafe;
@@ -141,7 +142,7 @@ mod tests {
 
     #[test]
     fn test_generator() {
-        let input = parse("7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9");
+        let input = parse("7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9").unwrap();
         assert_eq!(input,
                    vec![vec![7, 6, 4, 2, 1],
                         vec![1, 2, 7, 8, 9],
@@ -168,6 +169,8 @@ mod tests {
         assert_eq!(check_safety(&vec![1, 2, 3, 4, 5, 9]), Check::Unsafe);
         assert_eq!(check_safety(&vec![5, 1]), Check::Unsafe);
         assert_eq!(check_safety(&vec![1, 9]), Check::Unsafe);
+        assert_eq!(check_safety(&vec![3]), Check::Safe);
+        assert_eq!(check_safety(&vec![]), Check::Safe);
     }
     
     #[test]
@@ -196,6 +199,7 @@ mod tests {
 
         assert_eq!(check_safety_part2(&vec![1, 2, 3, 4, 5]), Check::Safe);
         assert_eq!(check_safety_part2(&vec![5, 4, 3, 2, 1]), Check::Safe);
+        assert_eq!(check_safety_part2(&vec![1, 9]), Check::Safe);
         assert_eq!(check_safety_part2(&vec![1, 2, 3, 4, 5, 5]), Check::Safe);
         assert_eq!(check_safety_part2(&vec![5, 4, 3, 2, 1, 1]), Check::Safe);
         assert_eq!(check_safety_part2(&vec![1, 2, 3, 4, 5, 5, 5]), Check::Unsafe);
@@ -2,28 +2,49 @@
 
 use std::collections::{HashMap, HashSet};
 use colored::Colorize;
+use crate::utils::Grid;
+use crate::parse::ParseError;
+use crate::search;
 
-type Input = Vec<Vec<char>>;
+type Input = Grid<char>;
 
 const INF: usize = 10000000;
 
-#[aoc_generator(day20)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| l.trim().chars().collect())
-        .collect()
+// Cheats can skip up to `radius` positions and only the ones saving at
+// least `threshold` picoseconds are counted.
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    radius: usize,
+    threshold: usize,
 }
 
-fn find(map: &Vec<Vec<char>>, c: char) -> (usize, usize) {
-    for i in 0 .. map.len() {
-        for j in 0 .. map[i].len() {
-            if map[i][j] == c {
-                return (j, i);
-            }
+impl Default for Config {
+    fn default() -> Self {
+        Self { radius: 2, threshold: 100 }
+    }
+}
+
+// Part 2 cheats last longer
+const PART2_RADIUS: usize = 20;
+
+#[aoc_generator(day20)]
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let grid = Grid::try_parse(20, input, "one of `.#SE`", |c| match c {
+        '.' | '#' | 'S' | 'E' => Some(c),
+        _ => None,
+    })?;
+
+    for c in ['S', 'E'] {
+        if grid.find(&c).is_none() {
+            return Err(ParseError::eof(20, input, &format!("a map with `{c}`")));
         }
     }
-    panic!();
+    Ok(grid)
+}
+
+fn find(map: &Input, c: char) -> (usize, usize) {
+    let (i, j) = map.find(&c).unwrap();
+    (j, i)
 }
 
 fn nbs(map: &Input, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
@@ -32,46 +53,25 @@ fn nbs(map: &Input, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
     if x > 0 && map[y][x-1] != '#' {
         result.push((x - 1, y));
     }
-    if x < map[y].len() - 1 && map[y][x+1] != '#' {
+    if x < map.width() - 1 && map[y][x+1] != '#' {
         result.push((x + 1, y));
     }
     if y > 0 && map[y-1][x] != '#' {
         result.push((x, y - 1));
     }
-    if y < map.len() - 1 && map[y+1][x] != '#' {
+    if y < map.height() - 1 && map[y+1][x] != '#' {
         result.push((x, y + 1));
     }
 
     result
 }
 
-fn build_distances(map: &Input, start: (usize, usize), end: (usize, usize)) -> Vec<Vec<usize>> {
+fn build_distances(map: &Input, start: (usize, usize), end: (usize, usize)) -> Grid<usize> {
+    let mut result = Grid::new(map.width(), map.height(), INF);
 
-    let mut result = Vec::<Vec<usize>>::with_capacity(map.len());
-
-    for y in 0 .. map.len() {
-        result.push(Vec::with_capacity(map[y].len()));
-        for x in 0 .. map[y].len() {
-            result[y].push(INF);
-        }
-    }
-
-    let mut pending = Vec::from([end]);
-    result[end.1][end.0] = 0;
-    
-    while let Some(p @ (x, y)) = pending.pop() {
-        let cur_d = result[y][x];
-        let nbss = nbs(map, p);
-
-        // println!("{p:?} {cur_d} {nbss:?}");
-        for n in nbss {
-            let (nx, ny) = n;
-            if cur_d + 1 < result[ny][nx] {
-                // println!(" +++ {n:?} {}", cur_d + 1);
-                result[ny][nx] = cur_d + 1;
-                pending.push(n);
-            }
-        }
+    let paths = search::bfs(end, |&p| nbs(map, p));
+    for (&(x, y), &d) in &paths.cost {
+        result[y][x] = d as usize;
     }
 
     result
@@ -81,8 +81,8 @@ fn get_nodes(map: &Input, (x, y): (usize, usize), radius: usize) -> Vec<(usize,
     let mut result = Vec::<(usize, usize)>::new();
 
     let radius = radius as i32;
-    let height = map.len() as i32;
-    let width = map[0].len() as i32;
+    let height = map.height() as i32;
+    let width = map.width() as i32;
 
     for ix in -radius ..= radius {
         for iy in -radius ..= radius {
@@ -116,11 +116,11 @@ fn dist((x1, y1): (usize, usize), (x2, y2): (usize, usize)) -> usize {
     ((x1 - x2).abs() + (y1 - y2).abs()) as usize
 }
 
-fn find_cheats(map: &Input, dm: &Vec<Vec<usize>>, start: (usize, usize), end: (usize, usize), radius: usize, min_cheat: usize) -> Vec<usize> {
+fn find_cheats(map: &Input, dm: &Grid<usize>, start: (usize, usize), end: (usize, usize), radius: usize, min_cheat: usize) -> Vec<usize> {
     let mut result = Vec::<usize>::new();
     
-    for y in 0 .. map.len() {
-        for x in 0 .. map[y].len() {
+    for y in 0 .. map.height() {
+        for x in 0 .. map.width() {
             if map[y][x] == '#' {
                 continue;
             }
@@ -148,26 +148,24 @@ fn find_cheats(map: &Input, dm: &Vec<Vec<usize>>, start: (usize, usize), end: (u
     result
 }
 
-#[aoc(day20, part1)]
-fn part1(input: &Input) -> usize {
+fn count_cheats(input: &Input, config: &Config) -> usize {
     let start = find(&input, 'S');
     let end = find(&input, 'E');
 
     let dist = build_distances(&input, start, end);
-    let cheats = find_cheats(&input, &dist, start, end, 2, 100);
+    let cheats = find_cheats(&input, &dist, start, end, config.radius, config.threshold);
 
     cheats.iter().count()
 }
 
+#[aoc(day20, part1)]
+fn part1(input: &Input) -> usize {
+    count_cheats(input, &Config::default())
+}
+
 #[aoc(day20, part2)]
 fn part2(input: &Input) -> usize {
-    let start = find(&input, 'S');
-    let end = find(&input, 'E');
-
-    let dist = build_distances(&input, start, end);
-    let cheats = find_cheats(&input, &dist, start, end, 20, 100);
-
-    cheats.iter().count()
+    count_cheats(input, &Config { radius: PART2_RADIUS, ..Config::default() })
 }
 
 
@@ -192,7 +190,7 @@ mod tests {
              #.#.#.#.#.#.###
              #...#...#...###
              ###############"
-        )
+        ).unwrap()
     }
     
     #[test]
@@ -212,8 +210,8 @@ mod tests {
         let end = find(&map, 'E');
         let dist = build_distances(&map, start, end);
 
-        for y in 0 .. map.len() {
-            for x in 0 .. map[y].len() {
+        for y in 0 .. map.height() {
+            for x in 0 .. map.width() {
                 if map[y][x] == '#' {
                     print!("(###)");
                 } else {
@@ -291,6 +289,14 @@ mod tests {
         assert_eq!(*m.get(&74).unwrap_or(&0), 4);
         assert_eq!(*m.get(&76).unwrap_or(&0), 3);
     }
-    
-}
 
+    #[test]
+    fn test_count_cheats() {
+        let map = sample_input();
+
+        assert_eq!(count_cheats(&map, &Config { threshold: 20, ..Config::default() }), 5);
+        assert_eq!(count_cheats(&map, &Config { threshold: 64, ..Config::default() }), 1);
+        assert_eq!(count_cheats(&map, &Config { threshold: 74, radius: PART2_RADIUS }), 7);
+        assert_eq!(count_cheats(&map, &Config { threshold: 76, radius: PART2_RADIUS }), 3);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::collections::{HashMap};
 use memoize::memoize;
+use crate::parse::{self, ParseError};
 
 type Input = Vec<Vec<char>>;
 
@@ -242,10 +243,17 @@ fn min_seq_length(numpad: NumpadType, keys: Vec<char>, level: u32) -> u64 {
 
 
 #[aoc_generator(day21)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| l.trim().chars().collect())
+fn parse(input: &str) -> Result<Input, ParseError> {
+    parse::lines(21, input)
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            line.chars()
+                .map(|(col, c)| match c {
+                    '0' ..= '9' | 'A' => Ok(c),
+                    _ => Err(line.error_at(col, c, "a numeric keypad key")),
+                })
+                .collect()
+        })
         .collect()
 }
 
@@ -279,7 +287,7 @@ mod tests {
              179A
              456A
              379A"
-        );
+        ).unwrap();
         assert_eq!(
             input,
             vec![
@@ -1,14 +1,27 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
 use std::collections::{HashMap};
+use crate::parse::{self, ParseError};
+use crate::viz::debug;
 
 type Input = Vec<u64>;
 
+// Secret numbers each buyer generates in a day
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    iterations: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { iterations: 2000 }
+    }
+}
+
 #[aoc_generator(day22)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| l.trim().parse().unwrap())
+fn parse(input: &str) -> Result<Input, ParseError> {
+    parse::lines(22, input)
+        .map(|line| line.parse(line.trim(), "a secret number"))
         .collect()
 }
 
@@ -83,9 +96,13 @@ fn price_commands(prices: Vec<i64>) -> HashMap<Vec<i64>, i64> {
 
 #[aoc(day22, part1)]
 fn part1(input: &Input) -> u64 {
+    part1_with(input, &Config::default())
+}
+
+fn part1_with(input: &Input, config: &Config) -> u64 {
     input
         .iter()
-        .map(|n| generate(*n, 2000))
+        .map(|n| generate(*n, config.iterations))
         .sum()
 }
 
@@ -147,14 +164,18 @@ fn experiments(input: &Input) -> i64 {
 
 #[aoc(day22, part2)]
 fn part2(input: &Input) -> i64 {
+    part2_with(input, &Config::default())
+}
+
+fn part2_with(input: &Input, config: &Config) -> i64 {
 
     let mut commands = Vec::<HashMap<Vec<i64>, i64>>::new();
 
-    println!("Generating...");
+    debug!("Generating...");
     for i in input {
-        commands.push(price_commands(prices(*i, 2000)));
+        commands.push(price_commands(prices(*i, config.iterations)));
     }
-    println!("Generating DONE");
+    debug!("Generating DONE");
 
     let mut max_val = 0;
     let mut max_seq = &Vec::<i64>::new();
@@ -175,11 +196,11 @@ fn part2(input: &Input) -> i64 {
                 max_seq = command;
             }
         }
-        println!("Check {} / {}. Max {}, {:?}",
+        debug!("Check {} / {}. Max {}, {:?}",
                  i+1, commands.len(), max_val, max_seq);
     }
 
-    println!("{max_seq:?} => {max_val}");
+    debug!("{max_seq:?} => {max_val}");
     max_val
 }
 
@@ -192,7 +213,7 @@ mod tests {
     fn test_parse() {
         let input = parse(
             "1\n10\n100\n2024"
-        );
+        ).unwrap();
         assert_eq!(input, vec![1, 10, 100, 2024]);
     }
 
@@ -231,7 +252,7 @@ mod tests {
     fn test_part1() {
         let input = parse(
             "1\n10\n100\n2024"
-        );
+        ).unwrap();
         assert_eq!(part1(&input), 37327623);
     }
 
@@ -287,7 +308,7 @@ mod tests {
     fn test_all_commands() {
         let input = parse(
             "1\n2\n3\n2024"
-        );
+        ).unwrap();
 
         assert_eq!(part2(&input), 23);
     }
@@ -296,7 +317,7 @@ mod tests {
     fn test_sample_1() {
         let input = parse(
             "2021\n5017\n19751"
-        );
+        ).unwrap();
 
         let tmp = vec![1,2,3,4,5,6,7,8];
         tmp[1..].windows(4).for_each(|w|{
@@ -311,8 +332,17 @@ mod tests {
     fn test_sample_2() {
         let input = parse(
             "5053\n10083\n11263"
-        );
+        ).unwrap();
 
         assert_eq!(part2(&input), 27);
     }
+
+    #[test]
+    fn test_iterations() {
+        // Ten secrets from 123, the best price is 6 after -1,-1,0,2
+        let config = Config { iterations: 10 };
+
+        assert_eq!(part1_with(&vec![123], &config), 5908254);
+        assert_eq!(part2_with(&vec![123], &config), 6);
+    }
 }
@@ -1,34 +1,35 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
 use std::collections::{HashSet, HashMap};
+use crate::parse::{self, ParseError};
 
 type Graph = HashMap<String, HashSet<String>>;
 type Set<T> = HashSet<T>;
 
 #[aoc_generator(day23)]
-fn parse(input: &str) -> Graph {
+fn parse(input: &str) -> Result<Graph, ParseError> {
     let mut result = HashMap::<String, Set<String>>::new();
-    input
-        .lines()
-        .for_each(|l| {
-            let p: Vec<_> = l.trim().split("-").collect();
-            let p0 = p[0].to_string();
-            let p1 = p[1].to_string();
-            
-            if result.contains_key(&p0) {
-                result.get_mut(&p0).unwrap().insert(p1.clone());
-            } else {
-                result.insert(p0.clone(), Set::<String>::from([p1.clone()]));
-            }
-
-            if result.contains_key(&p1) {
-                result.get_mut(&p1).unwrap().insert(p0.clone());
-            } else {
-                result.insert(p1.clone(), Set::<String>::from([p0.clone()]));
-            }
-        });
-    result
+    for line in parse::lines(23, input) {
+        let p = line.split_n("-", 2, "`-` and a computer name")?;
+        if let Some(name) = p.iter().find(|n| n.is_empty() || !n.chars().all(|c| c.is_ascii_alphanumeric())) {
+            return Err(line.error(name, "a computer name"));
+        }
+        let p0 = p[0].to_string();
+        let p1 = p[1].to_string();
         
+        if result.contains_key(&p0) {
+            result.get_mut(&p0).unwrap().insert(p1.clone());
+        } else {
+            result.insert(p0.clone(), Set::<String>::from([p1.clone()]));
+        }
+
+        if result.contains_key(&p1) {
+            result.get_mut(&p1).unwrap().insert(p0.clone());
+        } else {
+            result.insert(p1.clone(), Set::<String>::from([p0.clone()]));
+        }
+    }
+    Ok(result)
 }
 
 fn find_cycles_start_nodes(graph: &Graph, max_level: usize, start: Vec<String>) -> Set<Vec<String>> {
@@ -200,7 +201,7 @@ mod tests {
              wh-qp
              tb-vc
              td-yn"
-        )
+        ).unwrap()
     }
     
     #[test]
@@ -281,7 +282,7 @@ mod tests {
         c-f
         d-e
         d-f
-        e-f");
+        e-f").unwrap();
 
         let result = find_connected_nodes(&input);
 
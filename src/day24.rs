@@ -1,8 +1,18 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
-use std::collections::{HashSet, HashMap};
+mod circuit;
+
+use std::collections::{BTreeMap, HashSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::parse::{self, ParseError};
+use circuit::{Circuit, CircuitError};
+use crate::utils::Rng;
+use crate::viz::debug;
 
-const BITS: usize = 46;
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 enum Operand {
@@ -62,38 +72,42 @@ type OperationsResult = HashMap<String, Operation>;
 type Input = (Signals, Operations);
 
 #[aoc_generator(day24)]
-fn parse(input: &str) -> Input {
-    let mut lines = input.lines();
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let mut lines = parse::lines(24, input);
 
     let mut signals = Signals::new();
     let mut operands = Operations::new();
 
-    while let Some(l) = lines.next() {
-        let line = l.trim();
+    while let Some(line) = lines.next() {
         if line.is_empty() {
             break;
         }
 
-        let s: Vec<_> = line.split(": ").collect();
+        let s = line.split_n(": ", 2, "`<wire>: <value>`")?;
         let sname: String = String::from(s[0]);
-        let val: u8 = s[1].parse().unwrap();
+        let val: u8 = line.parse(s[1], "`0` or `1`")?;
+        if val > 1 {
+            return Err(line.error(s[1], "`0` or `1`"));
+        }
         signals.insert(sname, val);
     }
 
-    while let Some(l) = lines.next() {
-        let line = l.trim();
-
-        let s: Vec<_> = line.split(" -> ").collect();
+    while let Some(line) = lines.next() {
+        let s = line.split_n(" -> ", 2, "`-> <wire>`")?;
 
         let result = String::from(s[1]);
 
-        let s: Vec<_> = s[0].split(" ").collect();
+        let gate = s[0];
+        let s: Vec<_> = gate.split(" ").collect();
+        if s.len() != 3 {
+            return Err(line.error(gate, "`<wire> <gate> <wire>`"));
+        }
 
         let op = match s[1] {
             "AND" => AND,
             "XOR" => XOR,
             "OR"  => OR,
-            _     => panic!()
+            _     => return Err(line.error(s[1], "one of `AND`, `OR`, `XOR`")),
         };
         
         let in_a = String::from(s[0]);
@@ -114,7 +128,7 @@ fn parse(input: &str) -> Input {
         }
     }
     
-    (signals, operands)
+    Ok((signals, operands))
 }
 
 fn swap(swaps: &HashMap<String, String>, operation: Operation) -> Operation {
@@ -132,28 +146,9 @@ fn swap(swaps: &HashMap<String, String>, operation: Operation) -> Operation {
     result
 }
 
-fn eval_circuit(signals: &mut Signals, operations: &Operations, swaps: &HashMap<String, String>) {
-    let s2 = signals.clone();
-    let mut to_eval = Vec::<String>::from_iter(s2.keys().map(|s| s.clone()));
-    let mut evaluated = HashSet::<String>::from_iter(signals.keys().map(|s| s.clone()));
-
-    while let Some(s) = to_eval.pop() {
-        if let Some(ops) = operations.get(&s) {
-            ops.iter().for_each(|op| {
-                let op = swap(swaps, op.clone());
-                if !evaluated.contains(&op.result) && op.eval(signals) {
-                    evaluated.insert(op.result.clone());
-                    to_eval.push(op.result);
-                }
-            });
-        }
-    }
-}
-
 #[aoc(day24, part1)]
-fn part1((signals, operations): &Input) -> u64 {
-    let mut signals = signals.clone();
-    eval_circuit(&mut signals, &operations, &HashMap::new());
+fn part1((signals, operations): &Input) -> Result<u64, CircuitError> {
+    let signals = Circuit::new(operations, &HashMap::new())?.eval(signals);
 
 
     let mut zsig: Vec<_> = signals
@@ -171,7 +166,7 @@ fn part1((signals, operations): &Input) -> u64 {
         result += 2u64.pow(exp) * *v as u64;
         exp += 1;
     }
-    result
+    Ok(result)
 }
 
 fn reverse_operations(operations: &Operations, swaps: &HashMap<String, String>) -> OperationsResult {
@@ -204,27 +199,6 @@ fn dependencies(signal: String, rev_opers: &OperationsResult) -> HashSet<String>
     result
 }
 
-fn dependencies_with_value(signal: String, signals: &Signals, rev_opers: &OperationsResult) -> HashSet<String> {
-    let mut result = HashSet::<String>::new();
-    let mut pending = Vec::<&String>::new();
-    pending.push(&signal);
-
-    while let Some(s) = pending.pop() {
-        if let Some(op) = rev_opers.get(s) {
-            println!("+ {s}({}) <- {}({}) {:?} {}({})",
-                     signals.get(&op.result).unwrap_or(&0),
-                     &op.in_a, signals.get(&op.in_a).unwrap_or(&0),
-                     &op.op,
-                     &op.in_b, signals.get(&op.in_b).unwrap_or(&0));
-            result.insert(op.in_a.clone());
-            result.insert(op.in_b.clone());
-            pending.push(&op.in_b);
-            pending.push(&op.in_a);
-        }
-    }
-    result
-}
-
 fn to_b(num: u64, digits: usize) -> Vec<u8> {
     let mut result = Vec::new();
     // let digits = num.ilog2() + 1;
@@ -250,46 +224,10 @@ fn from_b(num: Vec<u8>) -> u64 {
 }
 
 
-// The problem is 45bit input and result 46bit
-fn execute(operations: &Operations, a: u64, b: u64, swaps: &HashMap<String, String>) -> (u64, Signals) {
-    let x = to_b(a, BITS);
-    let y = to_b(b, BITS);
-
-    // println!("X={x:?}, {}", from_b(x.clone()));
-    // println!("Y={y:?}, {}", from_b(y.clone()));
-
-    let mut signals = Signals::new();
-
-    for i in 0 .. x.len() {
-        let j = x.len() - i - 1;
-        signals.insert(format!("x{:02}", i), x[j]);
-    }
-
-    for i in 0 .. y.len() {
-        let j = y.len() - i - 1;
-        signals.insert(format!("y{:02}", i), y[j]);
-    }
-
-    eval_circuit(&mut signals, operations, swaps);
-
-    let mut z = Vec::<u8>::with_capacity(46);
-
-    for i in 0 .. BITS {
-        let i = BITS - i - 1;
-        let key = format!("z{:02}", i);
-        let v = *signals.get(&key).unwrap_or(&0);
-        z.push(v);
-    }
-
-    // println!("Z={z:?}, {}", from_b(z.clone()));
-
-    (from_b(z), signals)
-}
-
-
-// #[aoc(day24, part2)]
-fn show_bad_gates((signals, operations): &Input) -> u32 {
-    let kk: HashSet<_> = operations
+// Gates breaking the adder rules that are easy to check: only XOR gates
+// write z wires, and XOR gates not reading x and y write z wires
+fn suspect_gates(operations: &Operations) -> HashSet<&Operation> {
+    operations
         .iter()
         .flat_map(|(_, ops)| ops)
         .filter(|op| {
@@ -308,97 +246,396 @@ fn show_bad_gates((signals, operations): &Input) -> u32 {
                 
             false
         })
-        .collect();
+        .collect()
+}
 
-    for k in kk {
-        println!("{:?}", k);
+// #[aoc(day24, part2)]
+fn show_bad_gates((signals, operations): &Input) -> u32 {
+    for k in suspect_gates(operations) {
+        debug!("{:?}", k);
     }
     
     0
 }
 
-// I've solved this by using this method to display bad results and then
-// by hand checking the bas gates and replacing them.
-// The swaps map is a relationship of the gates to be replaced.
-#[aoc(day24, part2)]
-fn check_sums((signals, operations): &Input) -> String {
-    let mut swaps = HashMap::<String, String>::new();
-    swaps.insert(String::from("z08"), String::from("cdj"));
-    swaps.insert(String::from("cdj"), String::from("z08"));
-    
-    swaps.insert(String::from("z16"), String::from("mrb"));
-    swaps.insert(String::from("mrb"), String::from("z16"));
-    
-    swaps.insert(String::from("z32"), String::from("gfm"));
-    swaps.insert(String::from("gfm"), String::from("z32"));
-    
-    swaps.insert(String::from("qjd"), String::from("dhm"));
-    swaps.insert(String::from("dhm"), String::from("qjd"));
-    
-    let op_results = reverse_operations(&operations, &swaps);
+// An output bit of the adder that some of the additions get wrong
+#[derive(Debug, PartialEq)]
+struct FailingBit {
+    bit: usize,
+    failures: usize,
+    // The first addition getting it wrong
+    x: u64,
+    y: u64,
+    // Outputs of the gates the bit depends on, its own included
+    cone: Vec<String>,
+}
 
-    // let r = 0 ..= 2u64.pow(16);
-    // let r = [256];
+#[derive(Debug, PartialEq)]
+struct AdderReport {
+    additions: usize,
+    failing: Vec<FailingBit>,
+}
 
-    let mut x = 1;
-    
-    loop {
-        // let x = x / 100;
-        // let y = x % 100;
-        // let x = 0;
-        x *= 2;
-        let y = x;
+impl fmt::Display for AdderReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.failing.is_empty() {
+            return write!(f, "all bits right in {} additions", self.additions);
+        }
+        for failing in &self.failing {
+            let (x, y) = (failing.x, failing.y);
+            writeln!(
+                f,
+                "z{:02} wrong in {} of {} additions, first in {x} + {y} = {}",
+                failing.bit, failing.failures, self.additions, x.wrapping_add(y)
+            )?;
+            writeln!(f, "    cone: {}", failing.cone.join(", "))?;
+        }
+        Ok(())
+    }
+}
 
-        if x >= 2u64.pow(BITS as u32) {
-            break;
+// Additions exercising every bit: the bit alone in one or both operands,
+// carries rippling into it from bit 0 and all ones operands
+fn adder_tests(bits: usize) -> Vec<(u64, u64)> {
+    let mask = if bits == 0 { 0 } else { u64::MAX >> (64 - bits) };
+    let mut tests = vec![(0, 0), (mask, 0), (0, mask), (mask, 1), (1, mask), (mask, mask)];
+
+    for i in 0 .. bits {
+        let bit = 1 << i;
+        let below = bit - 1;
+        tests.extend([(bit, 0), (0, bit), (bit, bit), (below, 1), (1, below), (below | bit, 1), (below, bit | 1)]);
+    }
+
+    let mut seen = HashSet::new();
+    tests.retain(|test| seen.insert(*test));
+    tests
+}
+
+// Runs `adder_tests` on the circuit with the swaps applied, reporting each
+// wrong z bit with the gates it depends on
+fn verify_adder(operations: &Operations, swaps: &HashMap<String, String>) -> Result<AdderReport, CircuitError> {
+    let circuit = Circuit::new(operations, swaps)?;
+    let bits = circuit.width("x").max(circuit.width("y"));
+    let (x, y): (Vec<_>, Vec<_>) = adder_tests(bits).into_iter().unzip();
+    let z = circuit.simulate(&[("x", &x[..]), ("y", &y[..])], "z")?;
+
+    let op_results = reverse_operations(operations, swaps);
+    let mut failing = Vec::new();
+
+    for bit in 0 ..= bits.max(circuit.width("z").saturating_sub(1)) {
+        let wrong: Vec<_> = (0 .. z.len())
+            .filter(|i| (z[*i] ^ x[*i].wrapping_add(y[*i])) >> bit & 1 == 1)
+            .collect();
+        let Some(first) = wrong.first() else {
+            continue;
+        };
+
+        let wire = format!("z{bit:02}");
+        let mut cone: Vec<_> = dependencies(wire.clone(), &op_results)
+            .into_iter()
+            .chain([wire])
+            .filter(|w| op_results.contains_key(w))
+            .collect();
+        cone.sort();
+        failing.push(FailingBit { bit, failures: wrong.len(), x: x[*first], y: y[*first], cone });
+    }
+
+    Ok(AdderReport { additions: z.len(), failing })
+}
+
+// Number of random additions checked after repairing the adder
+const VERIFY_SAMPLES: usize = 100;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum AdderError {
+    MissingGate { op: Operand, in_a: String, in_b: String },
+    Ambiguous { op: Operand, in_a: String, in_b: String },
+    SwappedTwice(String),
+    WrongSum { x: u64, y: u64, z: u64 },
+    Circuit(CircuitError),
+}
+
+impl fmt::Display for AdderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdderError::MissingGate { op, in_a, in_b } => {
+                write!(f, "no {op:?} gate uses {in_a} or {in_b}, the circuit isn't a ripple-carry adder")
+            }
+            AdderError::Ambiguous { op, in_a, in_b } => {
+                write!(f, "no {op:?} gate for {in_a} and {in_b}, and more than one way to repair it")
+            }
+            AdderError::SwappedTwice(wire) => write!(f, "the output of {wire} would be swapped twice"),
+            AdderError::WrongSum { x, y, z } => write!(f, "after the swaps {x} + {y} gives {z}"),
+            AdderError::Circuit(e) => write!(f, "after the swaps {e}"),
         }
-        
-        // println!("=== {x} + {y} = {} ===", x + y);
-        
-        let (result, signals) = execute(&operations, x, y, &swaps);
+    }
+}
 
-        let real = to_b(result, BITS);
-        let expected = to_b(x + y, BITS);
+impl Error for AdderError {}
 
-        for i in 0 .. BITS {
-            if real[i] != expected[i] {
-                let j = BITS - i - 1;
-                println!("DIFF z{j:02} expected: {} but is {}", expected[i], real[i]);
-                let deps = dependencies_with_value(format!("z{j:02}"), &signals, &op_results);
-                println!("");
+impl From<CircuitError> for AdderError {
+    fn from(e: CircuitError) -> Self {
+        AdderError::Circuit(e)
+    }
+}
+
+// Output wires by gate kind and inputs, with the inputs sorted
+type Gates = HashMap<(Operand, String, String), String>;
+
+fn gate_key(op: Operand, a: &str, b: &str) -> (Operand, String, String) {
+    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+    (op, String::from(a), String::from(b))
+}
+
+fn gates(op_results: &OperationsResult) -> Gates {
+    op_results
+        .values()
+        .map(|op| (gate_key(op.op.clone(), &op.in_a, &op.in_b), op.result.clone()))
+        .collect()
+}
+
+// What stops the adder from being checked further
+enum Fault {
+    Swap(String, String),
+    Broken(AdderError),
+}
+
+// Output of the `op` gate for `a` and `b`. When there is none, one of the
+// inputs is right and the other one is the output of a swapped gate.
+fn find_gate(gates: &Gates, op: Operand, a: &str, b: &str) -> Result<String, Fault> {
+    if let Some(result) = gates.get(&gate_key(op.clone(), a, b)) {
+        return Ok(result.clone());
+    }
+
+    // Only gate outputs can be swapped, not the x and y inputs
+    let outputs: HashSet<_> = gates.values().map(|w| w.as_str()).collect();
+    let mut repairs = HashSet::new();
+    for (kind, in_a, in_b) in gates.keys().filter(|(kind, _, _)| *kind == op) {
+        for (good, bad) in [(a, b), (b, a)].into_iter().filter(|(_, bad)| outputs.contains(bad)) {
+            if in_a == good {
+                repairs.insert((String::from(bad), in_b.clone()));
+            }
+            if in_b == good {
+                repairs.insert((String::from(bad), in_a.clone()));
             }
         }
+    }
 
-        
-        if real != expected {
-            // println!(">>>>>> {}", &signals.get(&String::from("z32")).unwrap_or(&0));
-            // dependencies_with_value(format!("z32"), &signals, &op_results);
-            // 
-            // println!(">>>>>> {}", &signals.get(&String::from("z31")).unwrap_or(&0));
-            // dependencies_with_value(format!("z31"), &signals, &op_results);
-            // 
-            // println!(">>>>>> {}", &signals.get(&String::from("pqv")).unwrap_or(&0));
-            // dependencies_with_value(format!("pqv"), &signals, &op_results);
-            // 
-            // println!(">>>>>> {}", &signals.get(&String::from("gfm")).unwrap_or(&0));
-            // dependencies_with_value(format!("gfm"), &signals, &op_results);
-
-            // println!("{result}, {} ==> {:?}", x + 1, to_b(result ^ (x+y), BITS));
-            panic!();
+    let (in_a, in_b) = (String::from(a), String::from(b));
+    repairs.retain(|(_, good)| outputs.contains(good.as_str()));
+    match repairs.len() {
+        0 => Err(Fault::Broken(AdderError::MissingGate { op, in_a, in_b })),
+        1 => {
+            let (bad, good) = repairs.into_iter().next().unwrap();
+            Err(Fault::Swap(bad, good))
         }
+        _ => Err(Fault::Broken(AdderError::Ambiguous { op, in_a, in_b })),
+    }
+}
 
+fn expect_wire(found: String, expected: String) -> Result<(), Fault> {
+    if found == expected {
+        Ok(())
+    } else {
+        Err(Fault::Swap(found, expected))
     }
+}
 
-    // Output format: gates on alphabetical order
-    let mut gates: Vec<_> = swaps
-        .keys()
-        .map(|k| k.clone())
-        .collect();
+// Walks the adder from the lowest bit, each one must be:
+//
+//   x XOR y -> sum        sum XOR carry -> z
+//   x AND y -> and        sum AND carry -> carry_and
+//                         and OR carry_and -> next carry
+//
+// The first bit has no carry, so its sum is z and its and the carry. The
+// last carry is the highest z.
+fn check_adder(gates: &Gates, bits: usize) -> Result<(), Fault> {
+    let mut carry: Option<String> = None;
+
+    for i in 0 .. bits {
+        let (x, y, z) = (format!("x{i:02}"), format!("y{i:02}"), format!("z{i:02}"));
+        let sum = find_gate(gates, XOR, &x, &y)?;
+        let and = find_gate(gates, AND, &x, &y)?;
+
+        carry = Some(match carry {
+            None => {
+                expect_wire(sum, z)?;
+                and
+            }
+            Some(carry) => {
+                expect_wire(find_gate(gates, XOR, &sum, &carry)?, z)?;
+                let carry_and = find_gate(gates, AND, &sum, &carry)?;
+                find_gate(gates, OR, &and, &carry_and)?
+            }
+        });
+    }
+
+    match carry {
+        Some(carry) => expect_wire(carry, format!("z{bits:02}")),
+        None => Ok(()),
+    }
+}
+
+// Repairs the adder one swap at a time, each fix lets the check go further
+fn find_swaps(signals: &Signals, operations: &Operations) -> Result<HashMap<String, String>, AdderError> {
+    let bits = signals.keys().filter(|k| k.starts_with("x")).count();
+    let mut swaps = HashMap::<String, String>::new();
+
+    loop {
+        let gates = gates(&reverse_operations(operations, &swaps));
+        match check_adder(&gates, bits) {
+            Ok(()) => return Ok(swaps),
+            Err(Fault::Broken(e)) => return Err(e),
+            Err(Fault::Swap(a, b)) => {
+                if let Some(wire) = [&a, &b].into_iter().find(|w| swaps.contains_key(*w)) {
+                    return Err(AdderError::SwappedTwice(wire.clone()));
+                }
+                swaps.insert(a.clone(), b.clone());
+                swaps.insert(b, a);
+            }
+        }
+    }
+}
+
+fn verify_swaps(signals: &Signals, operations: &Operations, swaps: &HashMap<String, String>) -> Result<(), AdderError> {
+    let bits = signals.keys().filter(|k| k.starts_with("x")).count();
+    let circuit = Circuit::new(operations, swaps)?;
+    let mut rng = Rng::new(24);
+    let x: Vec<_> = (0 .. VERIFY_SAMPLES).map(|_| rng.below(1 << bits)).collect();
+    let y: Vec<_> = (0 .. VERIFY_SAMPLES).map(|_| rng.below(1 << bits)).collect();
+    let z = circuit.simulate(&[("x", &x[..]), ("y", &y[..])], "z")?;
+
+    match (0 .. z.len()).find(|i| z[*i] != x[*i] + y[*i]) {
+        Some(i) => Err(AdderError::WrongSum { x: x[i], y: y[i], z: z[i] }),
+        None => Ok(()),
+    }
+}
+
+#[aoc(day24, part2)]
+fn part2((signals, operations): &Input) -> Result<String, AdderError> {
+    let swaps = find_swaps(signals, operations)?;
+    verify_swaps(signals, operations, &swaps)?;
+
+    let mut wires: Vec<_> = swaps.into_keys().collect();
+    wires.sort();
+    Ok(wires.join(","))
+}
+
+
+// What `to_dot` draws in colour
+#[derive(Debug, Default)]
+struct Highlight {
+    // Outputs of the gates to mark
+    suspects: HashSet<String>,
+    // Applied before drawing, the swapped gates are linked with a dashed edge
+    swaps: HashMap<String, String>,
+}
+
+fn gate_shape(op: &Operand) -> &'static str {
+    match op {
+        AND => "box",
+        OR => "ellipse",
+        XOR => "diamond",
+    }
+}
 
-    gates.sort();
-    gates.join(",")
+fn bus_bit(wire: &str) -> Option<usize> {
+    wire.strip_prefix(['x', 'y', 'z'])?.parse().ok()
 }
 
+// Renders the gates as a Graphviz graph, a node per gate named after its
+// output. The inputs of each bit are on the same rank as the gates that
+// depend on them and no higher bit, so the carry chain goes down.
+fn to_dot(operations: &Operations, highlight: &Highlight) -> String {
+    let op_results = reverse_operations(operations, &highlight.swaps);
+    let mut gates: Vec<_> = op_results.values().collect();
+    gates.sort_by(|a, b| a.result.cmp(&b.result));
+
+    let mut inputs: Vec<_> = operations.keys().filter(|w| !op_results.contains_key(*w)).collect();
+    inputs.sort();
+
+    let mut ranks = BTreeMap::<usize, Vec<&String>>::new();
+    let mut dot = String::from("digraph circuit {\n    node [fontname=\"monospace\"];\n\n");
+
+    for wire in inputs {
+        dot.push_str(&format!("    \"{wire}\" [shape=plaintext];\n"));
+        if let Some(bit) = bus_bit(wire) {
+            ranks.entry(bit).or_default().push(wire);
+        }
+    }
+
+    for gate in &gates {
+        let wire = &gate.result;
+        let mut attrs = vec![
+            format!("shape={}", gate_shape(&gate.op)),
+            format!("label=\"{wire}\\n{:?}\"", gate.op),
+        ];
+        if wire.starts_with("z") {
+            attrs.push(String::from("peripheries=2"));
+        }
+        if highlight.suspects.contains(wire) {
+            attrs.push(String::from("style=filled, fillcolor=\"#ffb3b3\""));
+        } else if highlight.swaps.contains_key(wire) {
+            attrs.push(String::from("style=filled, fillcolor=\"#b3d1ff\""));
+        }
+        dot.push_str(&format!("    \"{wire}\" [{}];\n", attrs.join(", ")));
+
+        let bit = dependencies(wire.clone(), &op_results).iter().filter_map(|w| bus_bit(w)).max();
+        if let Some(bit) = bit {
+            ranks.entry(bit).or_default().push(wire);
+        }
+    }
+    dot.push('\n');
+
+    for gate in &gates {
+        for input in [&gate.in_a, &gate.in_b] {
+            dot.push_str(&format!("    \"{input}\" -> \"{}\";\n", gate.result));
+        }
+    }
+
+    let mut pairs: Vec<_> = highlight.swaps.iter().filter(|(a, b)| a < b).collect();
+    pairs.sort();
+    for (a, b) in pairs {
+        dot.push_str(&format!("    \"{a}\" -> \"{b}\" [style=dashed, dir=both, constraint=false, color=blue];\n"));
+    }
+    dot.push('\n');
+
+    for wires in ranks.values() {
+        let wires: Vec<_> = wires.iter().map(|w| format!("\"{w}\"")).collect();
+        dot.push_str(&format!("    {{ rank=same; {}; }}\n", wires.join("; ")));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn write_dot<P: AsRef<Path>>(path: P, operations: &Operations, highlight: &Highlight) -> io::Result<()> {
+    fs::write(path, to_dot(operations, highlight))
+}
+
+
+// Writes the signals and gates back in the puzzle format, the gates sorted
+// by output and with the outputs of the swapped gates exchanged
+fn to_text(signals: &Signals, operations: &Operations, swaps: &HashMap<String, String>) -> String {
+    let mut wires: Vec<_> = signals.iter().collect();
+    wires.sort();
+
+    let mut text = String::new();
+    for (wire, value) in wires {
+        text.push_str(&format!("{wire}: {value}\n"));
+    }
+    text.push('\n');
+
+    let mut gates: Vec<_> = reverse_operations(operations, swaps).into_values().collect();
+    gates.sort_by(|a, b| a.result.cmp(&b.result));
+    for gate in gates {
+        text.push_str(&format!("{} {:?} {} -> {}\n", gate.in_a, gate.op, gate.in_b, gate.result));
+    }
+    text
+}
+
+fn write_text<P: AsRef<Path>>(path: P, (signals, operations): &Input, swaps: &HashMap<String, String>) -> io::Result<()> {
+    fs::write(path, to_text(signals, operations, swaps))
+}
 
 #[cfg(test)]
 mod tests {
@@ -417,7 +654,7 @@ mod tests {
              x01 XOR y01 -> z01
              x02 OR y02 -> z02
              x00 AND x01 -> tmp"
-        )
+        ).unwrap()
     }
 
     fn sample_input_2() -> Input {
@@ -469,7 +706,7 @@ mod tests {
              hwm AND bqk -> z03
              tgd XOR rvg -> z12
              tnw OR pbm -> gnj"
-        )
+        ).unwrap()
     }
 
     #[test]
@@ -496,6 +733,15 @@ mod tests {
         assert_eq!(*operations.get(&String::from("y02")).unwrap(), vec![Operation::or(String::from("x02"), String::from("y02"), String::from("z02"))]);
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let err = parse("x00: 1\nx01: 2\n\nx00 AND x01 -> z00").unwrap_err();
+        assert_eq!(err, ParseError::new(24, 2, 6, "`0` or `1`", "2"));
+
+        let err = parse("x00: 1\nx01: 0\n\nx00 NAND x01 -> z00").unwrap_err();
+        assert_eq!(err, ParseError::new(24, 4, 5, "one of `AND`, `OR`, `XOR`", "NAND"));
+    }
+
     #[test]
     fn test_eval_and() {
         let mut signals = Signals::new();
@@ -524,8 +770,8 @@ mod tests {
 
     #[test]
     fn test_eval_circuit() {
-        let (mut signals, operations) = sample_input_1();
-        eval_circuit(&mut signals, &operations, &HashMap::new());
+        let (signals, operations) = sample_input_1();
+        let signals = Circuit::new(&operations, &HashMap::new()).unwrap().eval(&signals);
 
         assert_eq!(*signals.get(&String::from("z00")).unwrap(), 0);
         assert_eq!(*signals.get(&String::from("z01")).unwrap(), 0);
@@ -536,7 +782,7 @@ mod tests {
     fn test_part_1() {
         let input = sample_input_2();
         let result = part1(&input);
-        assert_eq!(result, 2024);
+        assert_eq!(result, Ok(2024));
     }
 
 
@@ -566,6 +812,209 @@ mod tests {
         ]));
     }
 
+    // Ripple-carry adder with the gate outputs of each pair in `swaps`
+    // exchanged, laid out like the puzzle inputs
+    pub(super) fn adder(bits: usize, swaps: &[(&str, &str)]) -> Input {
+        let mut text = String::new();
+        for i in 0 .. bits {
+            text.push_str(&format!("x{i:02}: 0\ny{i:02}: 1\n"));
+        }
+        text.push('\n');
+
+        let mut gates = Vec::new();
+        for i in 0 .. bits {
+            let carry_out = if i + 1 == bits { format!("z{bits:02}") } else { format!("k{i:02}") };
+            if i == 0 {
+                gates.push(String::from("x00 XOR y00 -> z00"));
+                gates.push(format!("y00 AND x00 -> {carry_out}"));
+                continue;
+            }
+            let carry = format!("k{:02}", i - 1);
+            gates.push(format!("x{i:02} XOR y{i:02} -> s{i:02}"));
+            gates.push(format!("y{i:02} AND x{i:02} -> a{i:02}"));
+            gates.push(format!("{carry} XOR s{i:02} -> z{i:02}"));
+            gates.push(format!("s{i:02} AND {carry} -> c{i:02}"));
+            gates.push(format!("a{i:02} OR c{i:02} -> {carry_out}"));
+        }
+
+        for gate in gates {
+            let (expr, result) = gate.split_once(" -> ").unwrap();
+            let result = swaps
+                .iter()
+                .find_map(|(a, b)| match result {
+                    _ if result == *a => Some(*b),
+                    _ if result == *b => Some(*a),
+                    _ => None,
+                })
+                .unwrap_or(result);
+            text.push_str(&format!("{expr} -> {result}\n"));
+        }
+        parse(&text).unwrap()
+    }
+
+    #[test]
+    fn test_part_2() {
+        assert_eq!(part2(&adder(8, &[])), Ok(String::new()));
+
+        let input = adder(12, &[("z03", "s03"), ("z06", "a06"), ("k08", "c09"), ("z10", "k10")]);
+        assert_eq!(part2(&input), Ok(String::from("a06,c09,k08,k10,s03,z03,z06,z10")));
+
+        // The carry into the last bit
+        let input = adder(6, &[("z05", "z06")]);
+        assert_eq!(part2(&input), Ok(String::from("z05,z06")));
+    }
+
+    #[test]
+    fn test_find_swaps_errors() {
+        let (signals, operations) = sample_input_2();
+        assert_eq!(
+            find_swaps(&signals, &operations),
+            Err(AdderError::MissingGate { op: XOR, in_a: String::from("x00"), in_b: String::from("y00") })
+        );
+
+        // Swapping two sums adds them in the wrong place
+        let (signals, operations) = adder(4, &[]);
+        let swaps = HashMap::from([
+            (String::from("z01"), String::from("z02")),
+            (String::from("z02"), String::from("z01")),
+        ]);
+        assert!(verify_swaps(&signals, &operations, &HashMap::new()).is_ok());
+        assert!(matches!(verify_swaps(&signals, &operations, &swaps), Err(AdderError::WrongSum { .. })));
+        assert_eq!(
+            AdderError::WrongSum { x: 1, y: 2, z: 5 }.to_string(),
+            "after the swaps 1 + 2 gives 5"
+        );
+
+        // The first carry feeds the gate that now writes it
+        let swaps = HashMap::from([
+            (String::from("k01"), String::from("z03")),
+            (String::from("z03"), String::from("k01")),
+        ]);
+        let err = verify_swaps(&signals, &operations, &swaps).unwrap_err();
+        assert!(matches!(err, AdderError::Circuit(CircuitError::Cycle(_))), "{err:?}");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let (_, operations) = parse("x00: 1\nx01: 1\ny00: 0\ny01: 1\n\n\
+                                     x00 AND y00 -> z00\n\
+                                     x01 XOR y01 -> z01\n\
+                                     z00 OR z01 -> z02").unwrap();
+        assert_eq!(to_dot(&operations, &Highlight::default()), r#"digraph circuit {
+    node [fontname="monospace"];
+
+    "x00" [shape=plaintext];
+    "x01" [shape=plaintext];
+    "y00" [shape=plaintext];
+    "y01" [shape=plaintext];
+    "z00" [shape=box, label="z00\nAND", peripheries=2];
+    "z01" [shape=diamond, label="z01\nXOR", peripheries=2];
+    "z02" [shape=ellipse, label="z02\nOR", peripheries=2];
+
+    "x00" -> "z00";
+    "y00" -> "z00";
+    "x01" -> "z01";
+    "y01" -> "z01";
+    "z00" -> "z02";
+    "z01" -> "z02";
+
+    { rank=same; "x00"; "y00"; "z00"; }
+    { rank=same; "x01"; "y01"; "z01"; "z02"; }
+}
+"#);
+
+        let highlight = Highlight {
+            suspects: HashSet::from([String::from("z00")]),
+            swaps: HashMap::from([
+                (String::from("z00"), String::from("z01")),
+                (String::from("z01"), String::from("z00")),
+            ]),
+        };
+        let dot = to_dot(&operations, &highlight);
+        assert!(dot.contains(r##""z00" [shape=diamond, label="z00\nXOR", peripheries=2, style=filled, fillcolor="#ffb3b3"];"##));
+        assert!(dot.contains(r##""z01" [shape=box, label="z01\nAND", peripheries=2, style=filled, fillcolor="#b3d1ff"];"##));
+        assert!(dot.contains(r#""x00" -> "z01";"#));
+        assert!(dot.contains(r#""z00" -> "z01" [style=dashed, dir=both, constraint=false, color=blue];"#));
+    }
+
+    #[test]
+    fn test_verify_adder() {
+        let (_, operations) = adder(8, &[]);
+        let report = verify_adder(&operations, &HashMap::new()).unwrap();
+        assert_eq!(report.failing, vec![]);
+        assert_eq!(report.to_string(), format!("all bits right in {} additions", adder_tests(8).len()));
+
+        // The x AND y of bit 3 and x XOR y of bit 5 exchanged
+        let (_, operations) = adder(8, &[("a03", "s05")]);
+        let report = verify_adder(&operations, &HashMap::new()).unwrap();
+        let bits: Vec<_> = report.failing.iter().map(|f| f.bit).collect();
+        assert_eq!(bits, vec![4, 5, 6, 7, 8]);
+        assert_eq!(report.failing[0].cone, vec![
+            "a01", "a02", "a03", "c01", "c02", "c03", "k00", "k01", "k02", "k03", "s01", "s02", "s03", "s04", "z04",
+        ]);
+        assert_eq!((report.failing[0].x, report.failing[0].y), (255, 0));
+
+        let swaps = HashMap::from([
+            (String::from("a03"), String::from("s05")),
+            (String::from("s05"), String::from("a03")),
+        ]);
+        assert_eq!(verify_adder(&operations, &swaps).unwrap().failing, vec![]);
+    }
+
+    #[test]
+    fn test_adder_report() {
+        let report = AdderReport {
+            additions: 20,
+            failing: vec![FailingBit {
+                bit: 3,
+                failures: 2,
+                x: 4,
+                y: 4,
+                cone: vec![String::from("a02"), String::from("z03")],
+            }],
+        };
+        assert_eq!(report.to_string(), "z03 wrong in 2 of 20 additions, first in 4 + 4 = 8\n    cone: a02, z03\n");
+    }
+
+    #[test]
+    fn test_to_text() {
+        let (signals, operations) = sample_input_1();
+        let text = to_text(&signals, &operations, &HashMap::new());
+        assert_eq!(text, "x00: 1\nx01: 1\nx02: 1\ny00: 0\ny01: 1\ny02: 0\n\n\
+                          x00 AND x01 -> tmp\n\
+                          x00 AND y00 -> z00\n\
+                          x01 XOR y01 -> z01\n\
+                          x02 OR y02 -> z02\n");
+
+        let swaps = HashMap::from([
+            (String::from("z00"), String::from("tmp")),
+            (String::from("tmp"), String::from("z00")),
+        ]);
+        let text = to_text(&signals, &operations, &swaps);
+        assert!(text.ends_with("x00 AND y00 -> tmp\nx00 AND x01 -> z00\nx01 XOR y01 -> z01\nx02 OR y02 -> z02\n"));
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        for (signals, operations) in [sample_input_1(), sample_input_2(), adder(10, &[("z03", "k05")])] {
+            let text = to_text(&signals, &operations, &HashMap::new());
+            let (parsed_signals, parsed_operations) = parse(&text).unwrap();
+
+            assert_eq!(parsed_signals, signals);
+            assert_eq!(
+                reverse_operations(&parsed_operations, &HashMap::new()),
+                reverse_operations(&operations, &HashMap::new())
+            );
+            assert_eq!(to_text(&parsed_signals, &parsed_operations, &HashMap::new()), text);
+        }
+
+        // Saving with the swaps gives the repaired adder
+        let (signals, operations) = adder(10, &[("a03", "s05"), ("z07", "c07")]);
+        let swaps = find_swaps(&signals, &operations).unwrap();
+        let (signals, operations) = parse(&to_text(&signals, &operations, &swaps)).unwrap();
+        assert_eq!(part2(&(signals, operations)), Ok(String::new()));
+    }
+
     #[test]
     fn test_binary_format() {
         assert_eq!(to_b(93, 7), vec![1, 0, 1, 1, 1, 0, 1]);
@@ -576,42 +1025,4 @@ mod tests {
         assert_eq!(from_b(to_b(39, 42)), 39);
         assert_eq!(from_b(to_b(140, 42)), 140);
     }
-
-    /*
-    #[test]
-    fn test_execute_sum() {
-        let (_, operations) = sample_input_2();
-
-        let mut swaps = HashMap::<String, String>::new();
-        // swaps.insert(String::from("fgs"), String::from("psh"));
-        // swaps.insert(String::from("psh"), String::from("fgs"));
-        
-        let op_results = reverse_operations(&operations, &swaps);
-
-        for i in vec![(0, 2)] {
-            println!("=== {x} + {y} = {} ===", x + y);
-            
-            let (result, signals) = execute(&operations, x, y, &swaps);
-
-            let real = to_b(result, BITS + 1);
-            let expected = to_b(x + y, BITS + 1);
-            
-            for i in 0 .. BITS + 1 {
-                if real[i] != expected[i] {
-                    let j = BITS + 1 - i - 1;
-                    println!("DIFF z{j:02} expected: {} but is {}", expected[i], real[i]);
-                    let deps = dependencies_with_value(format!("z{j:02}"), &signals, &op_results);
-                    println!("");
-                }
-            }
-
-            println!(">>>>>> {real:?} {}", &signals.get(&String::from("z00")).unwrap_or(&0));
-            let deps = dependencies_with_value(format!("z00"), &signals, &op_results);
-
-        }
-        
-        
-        todo!()
-}
-    */
 }
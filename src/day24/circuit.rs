@@ -0,0 +1,332 @@
+// The gates sorted once so every gate comes after the ones writing its
+// inputs, evaluated in a single pass. Each wire holds a u64 where every bit
+// is a separate evaluation, so 64 input vectors are evaluated at once.
+//
+// Buses are the wires named by a prefix and the bit number, like `x00`,
+// `x01`... They can have any prefix and any number of bits, their values
+// are stored in u64 words starting with the lowest bits.
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use super::{swap, Operand, Operations, Signals};
+
+// Evaluations packed in each word, and bits of a bus value in each word
+const LANES: usize = 64;
+const WORD: usize = 64;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CircuitError {
+    // The wires on the loop, starting with the lowest
+    Cycle(Vec<String>),
+    MultipleDrivers(String),
+    BusTooWide { prefix: String, width: usize },
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitError::Cycle(wires) => write!(f, "the gates loop through {}", wires.join(" -> ")),
+            CircuitError::MultipleDrivers(wire) => write!(f, "more than one gate writes {wire}"),
+            CircuitError::BusTooWide { prefix, width } => {
+                write!(f, "bus {prefix} has {width} bits, more than fit in a u64")
+            }
+        }
+    }
+}
+
+impl Error for CircuitError {}
+
+#[derive(Debug)]
+struct Gate {
+    op: Operand,
+    in_a: usize,
+    in_b: usize,
+    out: usize,
+}
+
+#[derive(Debug)]
+pub struct Circuit {
+    names: Vec<String>,
+    wires: HashMap<String, usize>,
+    // In evaluation order
+    gates: Vec<Gate>,
+}
+
+impl Circuit {
+    pub fn new(operations: &Operations, swaps: &HashMap<String, String>) -> Result<Self, CircuitError> {
+        let mut names = Vec::new();
+        let mut wires = HashMap::new();
+        let mut wire = |name: &String| {
+            *wires.entry(name.clone()).or_insert_with(|| {
+                names.push(name.clone());
+                names.len() - 1
+            })
+        };
+
+        // Each operation is listed under both of its inputs
+        let operations: HashSet<_> = operations.values().flatten().collect();
+        let mut gates = Vec::new();
+        let mut driven = HashSet::new();
+        for op in operations {
+            let op = swap(swaps, op.clone());
+            let gate = Gate { op: op.op, in_a: wire(&op.in_a), in_b: wire(&op.in_b), out: wire(&op.result) };
+            if !driven.insert(gate.out) {
+                return Err(CircuitError::MultipleDrivers(op.result));
+            }
+            gates.push(gate);
+        }
+
+        let gates = sort_gates(gates, names.len()).map_err(|cycle| {
+            let mut cycle: Vec<_> = cycle.into_iter().map(|w| names[w].clone()).collect();
+            let lowest = (0 .. cycle.len()).min_by_key(|i| &cycle[*i]).unwrap();
+            cycle.rotate_left(lowest);
+            CircuitError::Cycle(cycle)
+        })?;
+
+        Ok(Self { names, wires, gates })
+    }
+
+    // Wires of the bus by bit number, some may be missing
+    fn bus(&self, prefix: &str) -> Vec<Option<usize>> {
+        let mut bus = Vec::new();
+        for (name, wire) in &self.wires {
+            let Some(bit) = name
+                .strip_prefix(prefix)
+                .filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+                .and_then(|n| n.parse::<usize>().ok())
+            else {
+                continue;
+            };
+            if bus.len() <= bit {
+                bus.resize(bit + 1, None);
+            }
+            bus[bit] = Some(*wire);
+        }
+        bus
+    }
+
+    pub fn width(&self, prefix: &str) -> usize {
+        self.bus(prefix).len()
+    }
+
+    fn eval_lanes(&self, values: &mut [u64]) {
+        for gate in &self.gates {
+            let (a, b) = (values[gate.in_a], values[gate.in_b]);
+            values[gate.out] = match gate.op {
+                Operand::AND => a & b,
+                Operand::OR => a | b,
+                Operand::XOR => a ^ b,
+            };
+        }
+    }
+
+    // Value of every wire, the inputs missing in `signals` are 0
+    pub fn eval(&self, signals: &Signals) -> Signals {
+        let mut values = vec![0; self.names.len()];
+        for (name, value) in signals {
+            if let Some(wire) = self.wires.get(name) {
+                values[*wire] = *value as u64 & 1;
+            }
+        }
+
+        self.eval_lanes(&mut values);
+        self.names.iter().cloned().zip(values.iter().map(|v| *v as u8)).collect()
+    }
+
+    // Evaluates the circuit once for each index of the input slices, which
+    // must have the same length, and returns the values of the output bus.
+    // The buses not given are 0, the output has to fit in a u64.
+    pub fn simulate(&self, inputs: &[(&str, &[u64])], output: &str) -> Result<Vec<u64>, CircuitError> {
+        let width = self.width(output);
+        if width > WORD {
+            return Err(CircuitError::BusTooWide { prefix: String::from(output), width });
+        }
+
+        let inputs: Vec<(&str, Vec<Vec<u64>>)> = inputs
+            .iter()
+            .map(|(prefix, values)| (*prefix, values.iter().map(|v| vec![*v]).collect()))
+            .collect();
+        let inputs: Vec<_> = inputs.iter().map(|(prefix, values)| (*prefix, &values[..])).collect();
+        let result = self.simulate_wide(&inputs, output);
+        Ok(result.into_iter().map(|value| value.first().copied().unwrap_or(0)).collect())
+    }
+
+    // Same as `simulate` for buses of any width, each value is a list of
+    // words. The missing words of the inputs are 0, the output ones have
+    // as many as needed for its width.
+    pub fn simulate_wide(&self, inputs: &[(&str, &[Vec<u64>])], output: &str) -> Vec<Vec<u64>> {
+        let count = inputs.first().map_or(0, |(_, values)| values.len());
+        assert!(inputs.iter().all(|(_, values)| values.len() == count), "inputs of different lengths");
+
+        let buses: Vec<_> = inputs.iter().map(|(prefix, values)| (self.bus(prefix), *values)).collect();
+        let output = self.bus(output);
+        let words = output.len().div_ceil(WORD);
+        let mut result = Vec::with_capacity(count);
+
+        for start in (0 .. count).step_by(LANES) {
+            let lanes = LANES.min(count - start);
+            let mut values = vec![0; self.names.len()];
+
+            for (bus, operands) in &buses {
+                for (bit, wire) in bus.iter().enumerate() {
+                    let Some(wire) = wire else { continue };
+                    let (word, shift) = (bit / WORD, bit % WORD);
+                    values[*wire] = (0 .. lanes)
+                        .filter(|lane| operands[start + lane].get(word).is_some_and(|w| w >> shift & 1 == 1))
+                        .fold(0, |word, lane| word | 1 << lane);
+                }
+            }
+
+            self.eval_lanes(&mut values);
+
+            result.extend((0 .. lanes).map(|lane| {
+                let mut value = vec![0; words];
+                for (bit, wire) in output.iter().enumerate() {
+                    if let Some(w) = wire {
+                        value[bit / WORD] |= (values[*w] >> lane & 1) << (bit % WORD);
+                    }
+                }
+                value
+            }));
+        }
+        result
+    }
+}
+
+// Kahn's algorithm, on a cycle returns the wires on one of the loops
+fn sort_gates(gates: Vec<Gate>, wires: usize) -> Result<Vec<Gate>, Vec<usize>> {
+    let mut writer = vec![None; wires];
+    for (i, gate) in gates.iter().enumerate() {
+        writer[gate.out] = Some(i);
+    }
+
+    let mut readers = vec![Vec::new(); wires];
+    let mut pending = vec![0; gates.len()];
+    for (i, gate) in gates.iter().enumerate() {
+        for input in [gate.in_a, gate.in_b] {
+            if writer[input].is_some() {
+                readers[input].push(i);
+                pending[i] += 1;
+            }
+        }
+    }
+
+    let mut ready: Vec<_> = (0 .. gates.len()).filter(|i| pending[*i] == 0).collect();
+    let mut order = Vec::with_capacity(gates.len());
+    while let Some(i) = ready.pop() {
+        order.push(i);
+        for reader in &readers[gates[i].out] {
+            pending[*reader] -= 1;
+            if pending[*reader] == 0 {
+                ready.push(*reader);
+            }
+        }
+    }
+
+    if order.len() < gates.len() {
+        // Every gate left reads a wire of another gate left, going back
+        // through them has to repeat a gate
+        let mut seen = vec![None; gates.len()];
+        let mut path = Vec::new();
+        let mut i = (0 .. gates.len()).find(|i| pending[*i] > 0).unwrap();
+        while seen[i].is_none() {
+            seen[i] = Some(path.len());
+            path.push(gates[i].out);
+            let gate = &gates[i];
+            i = [gate.in_a, gate.in_b]
+                .into_iter()
+                .filter_map(|w| writer[w])
+                .find(|g| pending[*g] > 0)
+                .unwrap();
+        }
+        let mut cycle = path.split_off(seen[i].unwrap());
+        // Walked backwards, from readers to writers
+        cycle.reverse();
+        return Err(cycle);
+    }
+
+    let mut gates: Vec<_> = gates.into_iter().map(Some).collect();
+    Ok(order.into_iter().map(|i| gates[i].take().unwrap()).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day24::parse;
+    use crate::day24::tests::adder;
+    use crate::utils::Rng;
+
+    #[test]
+    fn test_eval() {
+        let (signals, operations) = parse("x00: 1\nx01: 1\ny00: 0\ny01: 1\n\n\
+                                           c XOR x01 -> z02\n\
+                                           x00 AND y00 -> z00\n\
+                                           x01 XOR y01 -> c\n\
+                                           z00 OR c -> z01").unwrap();
+        let circuit = Circuit::new(&operations, &HashMap::new()).unwrap();
+        let values = circuit.eval(&signals);
+
+        assert_eq!(values["c"], 0);
+        assert_eq!((values["z00"], values["z01"], values["z02"]), (0, 0, 1));
+        assert_eq!(circuit.width("z"), 3);
+        assert_eq!(circuit.width("w"), 0);
+    }
+
+    #[test]
+    fn test_simulate() {
+        let (_, operations) = adder(40, &[]);
+        let circuit = Circuit::new(&operations, &HashMap::new()).unwrap();
+        assert_eq!(circuit.width("x"), 40);
+        assert_eq!(circuit.width("z"), 41);
+
+        let mut rng = Rng::new(24);
+        let x: Vec<_> = (0 .. 150).map(|_| rng.below(1 << 40)).collect();
+        let y: Vec<_> = (0 .. 150).map(|_| rng.below(1 << 40)).collect();
+        let z = circuit.simulate(&[("x", &x[..]), ("y", &y[..])], "z").unwrap();
+
+        let expected: Vec<_> = x.iter().zip(&y).map(|(a, b)| a + b).collect();
+        assert_eq!(z, expected);
+        assert_eq!(circuit.simulate(&[("x", &x[.. 3])], "z").unwrap(), &x[.. 3]);
+    }
+
+    #[test]
+    fn test_simulate_wide() {
+        let (_, operations) = adder(100, &[]);
+        let circuit = Circuit::new(&operations, &HashMap::new()).unwrap();
+        assert_eq!(circuit.width("z"), 101);
+
+        let mut rng = Rng::new(24);
+        let mut number = || (rng.below(1 << 50) as u128) << 50 | rng.below(1 << 50) as u128;
+        let x: Vec<u128> = (0 .. 150).map(|_| number()).collect();
+        let y: Vec<u128> = (0 .. 150).map(|_| number()).collect();
+        let words = |n: u128| vec![n as u64, (n >> 64) as u64];
+
+        let xs: Vec<_> = x.iter().map(|n| words(*n)).collect();
+        let ys: Vec<_> = y.iter().map(|n| words(*n)).collect();
+        let z = circuit.simulate_wide(&[("x", &xs[..]), ("y", &ys[..])], "z");
+
+        let expected: Vec<_> = x.iter().zip(&y).map(|(a, b)| words(a + b)).collect();
+        assert_eq!(z, expected);
+        // The missing words are 0
+        assert_eq!(circuit.simulate_wide(&[("x", &[vec![5]][..])], "z"), vec![vec![5, 0]]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let (_, operations) = parse("x00: 1\n\nx00 AND b -> a\na OR x00 -> c\nc XOR x00 -> b\nb AND a -> z00").unwrap();
+        let err = Circuit::new(&operations, &HashMap::new()).unwrap_err();
+        assert_eq!(err, CircuitError::Cycle(vec![String::from("a"), String::from("c"), String::from("b")]));
+        assert_eq!(err.to_string(), "the gates loop through a -> c -> b");
+
+        let (_, operations) = parse("x00: 1\n\nx00 AND x01 -> z00\nx00 OR x01 -> z00").unwrap();
+        let err = Circuit::new(&operations, &HashMap::new()).unwrap_err();
+        assert_eq!(err, CircuitError::MultipleDrivers(String::from("z00")));
+
+        let (_, operations) = parse("x00: 1\n\nx00 AND x64 -> z64").unwrap();
+        let circuit = Circuit::new(&operations, &HashMap::new()).unwrap();
+        let err = circuit.simulate(&[("x", &[1][..])], "z").unwrap_err();
+        assert_eq!(err, CircuitError::BusTooWide { prefix: String::from("z"), width: 65 });
+        assert_eq!(err.to_string(), "bus z has 65 bits, more than fit in a u64");
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(unused_variables, unused_mut, dead_code, unused_comparisons, unused_imports)]
 
 use std::collections::{HashSet, HashMap};
+use crate::parse::{self, Line, ParseError};
 
 type Input = Vec<Entry>;
 
@@ -43,20 +44,44 @@ fn parse_one(lines: &[&str]) -> Entry {
     }
 }
 
+// Checks the shape of a schematic before reading its heights
+fn check_schematic(input: &str, lines: &[Line]) -> Result<(), ParseError> {
+    if lines.len() < 7 {
+        return Err(ParseError::eof(25, input, "a schematic of 7 rows"));
+    }
+
+    let first = lines[0].trim();
+    if first != "#####" && first != "....." {
+        return Err(lines[0].error(first, "`#####` or `.....`"));
+    }
+
+    for line in lines {
+        for (col, c) in line.chars() {
+            if c != '#' && c != '.' {
+                return Err(line.error_at(col, c, "`#` or `.`"));
+            }
+        }
+        if line.trim().len() != 5 {
+            return Err(line.error(line.trim(), "row of 5 cells"));
+        }
+    }
+    Ok(())
+}
+
 #[aoc_generator(day25)]
-fn parse(input: &str) -> Input {
-    let v: Vec<_> = input
-        .lines()
-        .map(|l| l.trim())
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let v: Vec<Line> = parse::lines(25, input)
         .filter(|l| !l.is_empty())
         .collect();
 
     let mut result = Input::new();
     for c in v.chunks(7) {
-        result.push(parse_one(c));
+        check_schematic(input, c)?;
+        let rows: Vec<&str> = c.iter().map(|l| l.trim()).collect();
+        result.push(parse_one(&rows));
     }
 
-    result
+    Ok(result)
 }
 
 fn fit(key: &Entry, lock: &Entry) -> bool {
@@ -142,7 +167,7 @@ mod tests {
              #.#..
              #.#.#
              #####"
-        )
+        ).unwrap()
     }
 
     #[test]
@@ -1,55 +1,34 @@
 #![allow(unused_variables, unused_mut, dead_code)]
 
-use crate::utils::get2d;
+use crate::utils::Grid;
+use crate::parse::ParseError;
 
-type Input = Vec<Vec<char>>;
+type Input = Grid<char>;
 
 #[aoc_generator(day4)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| {
-            l.trim().chars().collect()
-        })
-        .collect()
+fn parse(input: &str) -> Result<Input, ParseError> {
+    Grid::try_parse(4, input, "a letter", Some)
 }
 
-// Safely access the array of inputs
-// fn get(input: &Input, i: i32, j: i32) -> &char {
-//     if let Ok(i) = usize::try_from(i) {
-//         if let Some(v2) = input.get(i) {
-//             if let Ok(j) = usize::try_from(j) {
-//                 v2.get(j).unwrap_or(&'.')
-//             } else {
-//                 &'.'
-//             }
-//         } else {
-//             &'.'
-//         }
-//     } else {
-//         &'.'
-//     }
-// }
-
 fn letters(input: &Input, i: i32, j: i32, length: i32) -> Vec<String> {
     vec![
-        (0..length).map(|a| get2d(input, i, j + a)).collect(),
-        (0..length).map(|a| get2d(input, i + a, j)).collect(),
-        (0..length).map(|a| get2d(input, i, j - a)).collect(),
-        (0..length).map(|a| get2d(input, i - a, j)).collect(),
-
-        (0..length).map(|a| get2d(input, i + a, j + a)).collect(),
-        (0..length).map(|a| get2d(input, i - a, j + a)).collect(),
-        (0..length).map(|a| get2d(input, i - a, j - a)).collect(),
-        (0..length).map(|a| get2d(input, i + a, j - a)).collect(),
+        (0..length).map(|a| input.get_or(i, j + a, &' ')).collect(),
+        (0..length).map(|a| input.get_or(i + a, j, &' ')).collect(),
+        (0..length).map(|a| input.get_or(i, j - a, &' ')).collect(),
+        (0..length).map(|a| input.get_or(i - a, j, &' ')).collect(),
+
+        (0..length).map(|a| input.get_or(i + a, j + a, &' ')).collect(),
+        (0..length).map(|a| input.get_or(i - a, j + a, &' ')).collect(),
+        (0..length).map(|a| input.get_or(i - a, j - a, &' ')).collect(),
+        (0..length).map(|a| input.get_or(i + a, j - a, &' ')).collect(),
     ]
 }
 
 #[aoc(day4, part1)]
 fn part1(input: &Input) -> u32 {
     let mut result: u32 = 0;
-    for i in 0 .. input.len() {
-        for j in 0 .. input[0].len() {
+    for i in 0 .. input.height() {
+        for j in 0 .. input.width() {
             let letters = letters(input, i as i32, j as i32, 4);
             // println!("{i}, {j} = {letters:?}");
             result += letters.iter().filter(|s| *s == "XMAS").count() as u32;
@@ -60,15 +39,15 @@ fn part1(input: &Input) -> u32 {
 
 fn letters_2(input: &Input, i: i32, j: i32, length: i32) -> Vec<String> {
     let v1: Vec<&char> =
-        vec![get2d(input, i - 1, j - 1),
-             get2d(input, i, j),
-             get2d(input, i + 1, j + 1),
+        vec![input.get_or(i - 1, j - 1, &' '),
+             input.get_or(i, j, &' '),
+             input.get_or(i + 1, j + 1, &' '),
         ];
 
     let v2 =
-        vec![get2d(input, i - 1, j + 1),
-             get2d(input, i, j),
-             get2d(input, i + 1, j - 1)
+        vec![input.get_or(i - 1, j + 1, &' '),
+             input.get_or(i, j, &' '),
+             input.get_or(i + 1, j - 1, &' ')
         ];
 
     
@@ -79,8 +58,8 @@ fn letters_2(input: &Input, i: i32, j: i32, length: i32) -> Vec<String> {
 #[aoc(day4, part2)]
 fn part2(input: &Input) -> u32 {
     let mut result: u32 = 0;
-    for i in 0 .. input.len() {
-        for j in 0 .. input[0].len() {
+    for i in 0 .. input.height() {
+        for j in 0 .. input.width() {
             let letters = letters_2(input, i as i32, j as i32, 4);
 
             if (letters[0] == "MAS" || letters[0] == "SAM") && (letters[1] == "MAS" || letters[1] == "SAM"){
@@ -103,7 +82,7 @@ mod tests {
                            SSAMXM
                            SAXMAA
                            XMASMS
-                           XXSAMS");
+                           XXSAMS").unwrap();
 
         assert_eq!(input[0][0], 'S');
         assert_eq!(input[0][1], 'M');
@@ -126,7 +105,7 @@ mod tests {
                            SSAMXM
                            SAXMAA
                            XMASMS
-                           XXSAMS");
+                           XXSAMS").unwrap();
 
         let out = letters(&input, 0, 0, 5);
         assert_eq!(out, vec![
@@ -146,7 +125,7 @@ mod tests {
                            .SAMX.
                            .A..A.
                            XMAS.S
-                           .X....");
+                           .X....").unwrap();
 
         assert_eq!(part1(&input), 4);
     }
@@ -162,7 +141,7 @@ mod tests {
                            SMSMSASXSS
                            SAXAMASAAA
                            MAMMMXMMMM
-                           MXMXAXMASX");
+                           MXMXAXMASX").unwrap();
 
         assert_eq!(part1(&input), 18);
     }
@@ -178,7 +157,7 @@ mod tests {
                            SMSMSASXSS
                            SAXAMASAAA
                            MAMMMXMMMM
-                           MXMXAXMASX");
+                           MXMXAXMASX").unwrap();
 
         assert_eq!(part2(&input), 9);
     }
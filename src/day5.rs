@@ -4,24 +4,26 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::cmp::Ordering;
 
+use crate::parse::{self, ParseError};
+
 type Input = (HashMap<u32, HashSet<u32>>, Vec<Vec<u32>>);
 
 
 #[aoc_generator(day5)]
-fn parse(input: &str) -> Input {
+fn parse(input: &str) -> Result<Input, ParseError> {
     let mut rules = HashMap::new();
     let mut lists = Vec::new();
 
-    let mut lines_it = input.lines().map(|l| l.trim());
+    let mut lines_it = parse::lines(5, input);
 
     while let Some(line) = lines_it.next() {
-        if line == "" {
+        if line.is_empty() {
             break;
         }
         
-        let sp: Vec<&str> = line.split("|").collect();
-        let n: u32 = sp[0].parse().unwrap();
-        let m: u32 = sp[1].parse().unwrap();
+        let sp = line.split_n("|", 2, "`|` and a page number")?;
+        let n: u32 = line.parse(sp[0], "a page number")?;
+        let m: u32 = line.parse(sp[1], "a page number")?;
 
         if rules.contains_key(&n) {
             let mut cval: &mut HashSet<_> = rules.get_mut(&n).unwrap();
@@ -34,11 +36,14 @@ fn parse(input: &str) -> Input {
     }
 
     while let Some(line) = lines_it.next() {
-        lists.push(
-            line.split(",").map(|n| n.parse::<u32>().unwrap()).collect()
-        );
+        let list: Result<Vec<u32>, _> = line
+            .trim()
+            .split(",")
+            .map(|n| line.parse(n, "a page number"))
+            .collect();
+        lists.push(list?);
     }
-    (rules, lists)
+    Ok((rules, lists))
 }
 
 fn check(rules: &HashMap<u32, HashSet<u32>>, processed: &HashSet<u32>, elem: &u32) -> bool {
@@ -152,7 +157,7 @@ mod tests {
              75,29,13
              75,97,47,61,53
              61,13,29
-             97,13,75,29,47");
+             97,13,75,29,47").unwrap();
 
         let set75 = input.0.get(&75).unwrap();
         assert!(set75.contains(&29));
@@ -207,7 +212,7 @@ mod tests {
              75,29,13
              75,97,47,61,53
              61,13,29
-             97,13,75,29,47");
+             97,13,75,29,47").unwrap();
 
 
         assert!(check(&input.0, &vec![29, 53].into_iter().collect(), &75));
@@ -246,7 +251,7 @@ mod tests {
              75,29,13
              75,97,47,61,53
              61,13,29
-             97,13,75,29,47");
+             97,13,75,29,47").unwrap();
 
         assert_eq!(part1(&input), 143);
     }
@@ -281,7 +286,7 @@ mod tests {
              75,29,13
              75,97,47,61,53
              61,13,29
-             97,13,75,29,47");
+             97,13,75,29,47").unwrap();
 
         assert_eq!(part2(&input), 123);
     }
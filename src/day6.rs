@@ -1,9 +1,14 @@
 #![allow(unused_variables, unused_mut, dead_code)]
 
-use crate::utils::*;
+use crate::utils::Grid;
+use crate::parse::ParseError;
+use crate::viz::{self, Frame, Style};
+use crate::export::{Recorder, Rgb, BACKGROUND};
 use std::collections::HashSet;
+use std::error::Error;
+use std::io;
 
-type Input = Vec<Vec<char>>;
+type Input = Grid<char>;
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 enum Dir {
@@ -11,24 +16,21 @@ enum Dir {
 }
 
 #[aoc_generator(day6)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| l.trim().chars().collect())
-        .collect()
+fn parse(input: &str) -> Result<Input, ParseError> {
+    let grid = Grid::try_parse(6, input, "one of `.#^`", |c| match c {
+        '.' | '#' | '^' => Some(c),
+        _ => None,
+    })?;
+
+    if grid.find(&'^').is_none() {
+        return Err(ParseError::eof(6, input, "a guard `^`"));
+    }
+    Ok(grid)
 }
 
 fn find_start(input: &Input) -> (i32, i32) {
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
-            let i = i as i32;
-            let j = j as i32;
-            if get2d(input, i, j) == &'^' {
-                return (i, j)
-            }
-        }
-    }
-    unreachable!()
+    let (i, j) = input.find(&'^').unwrap();
+    (i as i32, j as i32)
 }
 
 fn rotate(dir: Dir) -> Dir {
@@ -50,7 +52,7 @@ fn sim_step(input: &Input, position: (i32, i32), dir: Dir) -> Option<((i32, i32)
         Dir::W => j = j - 1,
     };
 
-    match *get2d(input, i, j) {
+    match *input.get_or(i, j, &' ') {
         '.' | '^' => Some(((i, j), dir)),
         '#' => Some((position, rotate(dir))),
         _ => None
@@ -70,7 +72,7 @@ fn sim_step_2(input: &Input, position: (i32, i32), dir: Dir, obstacle: &(i32, i3
     if obstacle.0 == i && obstacle.1 == j {
         Some((position, rotate(dir)))
     } else {
-        match *get2d(input, i, j) {
+        match *input.get_or(i, j, &' ') {
             '.' | '^' => Some(((i, j), dir)),
             '#' => Some((position, rotate(dir))),
             _ => None
@@ -78,28 +80,85 @@ fn sim_step_2(input: &Input, position: (i32, i32), dir: Dir, obstacle: &(i32, i3
     }
 }
 
-fn print_sim(input: &Input, position: (i32, i32), processed: HashSet<(i32, i32)>) {
-    println!("=======");
+// The map with the guard and the positions it already visited
+struct Sim<'a> {
+    input: &'a Input,
+    position: (i32, i32),
+    processed: &'a HashSet<(i32, i32)>,
+}
 
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
-            let i = i as i32;
-            let j = j as i32;
-            let c = get2d(input, i, j);
-
-            if (i, j) == position {
-                print!("*");
-            } else if processed.contains(&(i, j)) {
-                print!("X");
-            } else {
-                print!("{c}");
-            }
-            
+impl<'a> Sim<'a> {
+    fn new(input: &'a Input, position: (i32, i32), processed: &'a HashSet<(i32, i32)>) -> Self {
+        Self { input, position, processed }
+    }
+}
+
+impl Frame for Sim<'_> {
+    fn height(&self) -> usize {
+        self.input.height()
+    }
+
+    fn width(&self) -> usize {
+        self.input.width()
+    }
+
+    fn cell(&self, i: usize, j: usize) -> (char, Style) {
+        let pos = (i as i32, j as i32);
+        let c = self.input[(i, j)];
+
+        if pos == self.position {
+            ('*', Style::Agent)
+        } else if self.processed.contains(&pos) {
+            ('X', Style::Path)
+        } else if c == '#' {
+            ('#', Style::Wall)
+        } else {
+            (c, Style::Plain)
         }
-        println!();
+    }
+
+    fn caption(&self) -> Option<String> {
+        Some(String::from("======="))
+    }
+}
+
+fn print_sim(input: &Input, position: (i32, i32), processed: HashSet<(i32, i32)>) {
+    viz::print(&Sim::new(input, position, &processed));
+}
+
+fn path_color(c: char, _style: Style) -> Rgb {
+    match c {
+        '#' => [90, 90, 90],
+        'X' => [200, 50, 50],
+        '*' => [60, 200, 90],
+        _ => BACKGROUND,
     }
 }
 
+// Records a frame for every step of the guard until it leaves the map
+fn record_path(input: &Input, recorder: &mut Recorder) -> io::Result<()> {
+    let mut position = find_start(input);
+    let mut processed = HashSet::from([position]);
+    let mut dir = Dir::N;
+
+    recorder.add_frame(&Sim::new(input, position, &processed), path_color)?;
+
+    while let Some((new_position, new_dir)) = sim_step(input, position, dir) {
+        position = new_position;
+        dir = new_dir;
+        processed.insert(position);
+
+        recorder.add_frame(&Sim::new(input, position, &processed), path_color)?;
+    }
+    Ok(())
+}
+
+// Records the path of a puzzle input, run by `aoc-2024 record`
+pub(crate) fn record_input(text: &str, recorder: &mut Recorder) -> Result<(), Box<dyn Error>> {
+    record_path(&parse(text)?, recorder)?;
+    Ok(())
+}
+
 #[aoc(day6, part1)]
 fn part1(input: &Input) -> i32 {
     let mut position = find_start(input);
@@ -145,8 +204,8 @@ fn part2(input: &Input) -> i32 {
     let mut position = find_start(input);
     let mut loops = 0;
 
-    for i in 0 .. input.len() {
-        for j in 0 .. input[i].len() {
+    for i in 0 .. input.height() {
+        for j in 0 .. input.width() {
             let i = i as i32;
             let j = j as i32;
 
@@ -182,7 +241,7 @@ mod tests {
                     .#..^.....
                     ........#.
                     #.........
-                    ......#..."));
+                    ......#...").unwrap());
 
         assert_eq!(i, 6);
         assert_eq!(j, 4);
@@ -199,13 +258,13 @@ mod tests {
                            .#..^.....
                            ........#.
                            #.........
-                           ......#...");
+                           ......#...").unwrap();
 
-        assert_eq!(get2d(&input, 0, 0), &'.');
-        assert_eq!(get2d(&input, 0, 4), &'#');
-        assert_eq!(get2d(&input, 6, 4), &'^');
-        assert_eq!(get2d(&input, 10, 4), &' ');
-        assert_eq!(get2d(&input, 6, 10), &' ');
+        assert_eq!(input.get(0, 0), Some(&'.'));
+        assert_eq!(input.get(0, 4), Some(&'#'));
+        assert_eq!(input.get(6, 4), Some(&'^'));
+        assert_eq!(input.get(10, 4), None);
+        assert_eq!(input.get(6, 10), None);
     }
 
     #[test]
@@ -219,11 +278,21 @@ mod tests {
                            .#..^.....
                            ........#.
                            #.........
-                           ......#...");
+                           ......#...").unwrap();
 
         assert_eq!(part1(&input), 41);
     }
 
+    #[test]
+    fn test_record_path() {
+        let input = parse("#..\n...\n^..").unwrap();
+        let mut recorder = Recorder::new(1);
+        record_path(&input, &mut recorder).unwrap();
+
+        // Start, one step north, the turn and two steps east
+        assert_eq!(recorder.len(), 5);
+    }
+
     #[test]
     fn test_part2() {
         let input = parse("....#.....
@@ -235,7 +304,7 @@ mod tests {
                            .#..^.....
                            ........#.
                            #.........
-                           ......#...");
+                           ......#...").unwrap();
 
         assert_eq!(part2(&input), 6);
     }
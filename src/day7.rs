@@ -1,16 +1,24 @@
 #![allow(unused_variables, unused_mut, dead_code)]
 
+use crate::parse::{self, ParseError};
+
 type Input = Vec<(i64, Vec<i64>)>;
 
 #[aoc_generator(day7)]
-fn parse(input: &str) -> Input {
-    input
-        .lines()
-        .map(|l| {
-            let ss: Vec<&str> = l.trim().split(": ").collect();
-            let target = ss[0].parse().unwrap();
-            let ns: Vec<i64> = ss[1].split_whitespace().map(|n| n.parse::<i64>().unwrap()).collect();
-            (target, ns)
+fn parse(input: &str) -> Result<Input, ParseError> {
+    parse::lines(7, input)
+        .map(|line| {
+            let ss = line.split_n(": ", 2, "`: ` followed by the numbers")?;
+            let target = line.parse(ss[0], "the test value")?;
+            let ns: Vec<i64> = ss[1]
+                .split_whitespace()
+                .map(|n| line.parse(n, "a number"))
+                .collect::<Result<_, _>>()?;
+
+            if ns.is_empty() {
+                return Err(line.error_end("a number"));
+            }
+            Ok((target, ns))
         })
         .collect()
 }
@@ -97,7 +105,7 @@ mod tests {
                      161011: 16 10 13
                      192: 17 8 14
                      21037: 9 7 18 13
-                     292: 11 6 16 20");
+                     292: 11 6 16 20").unwrap();
 
         assert_eq!(input[0].0, 190);
         assert_eq!(input[0].1, vec![10, 19]);
@@ -127,7 +135,7 @@ mod tests {
                      161011: 16 10 13
                      192: 17 8 14
                      21037: 9 7 18 13
-                     292: 11 6 16 20");
+                     292: 11 6 16 20").unwrap();
 
         assert_eq!(part1(&input), 3749);
     }
@@ -158,7 +166,7 @@ mod tests {
                      161011: 16 10 13
                      192: 17 8 14
                      21037: 9 7 18 13
-                     292: 11 6 16 20");
+                     292: 11 6 16 20").unwrap();
 
         assert_eq!(part2(&input), 11387);
     }
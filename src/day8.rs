@@ -3,23 +3,25 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use crate::utils::Grid;
+use crate::parse::ParseError;
+
 type Input = (i32, i32, HashMap<char, Vec<(i32, i32)>>);
 
 #[aoc_generator(day8)]
-fn parse(input: &str) -> Input {
+fn parse(input: &str) -> Result<Input, ParseError> {
     let mut entries = HashMap::new();
 
-    let lines: Vec<_> = input.lines().map(|l| l.trim()).collect();
-
-    let height = lines.len() as i32;
-    let width = lines[0].len() as i32;
+    let grid = Grid::try_parse(8, input, "`.` or an antenna frequency", |c| {
+        (c == '.' || c.is_ascii_alphanumeric()).then_some(c)
+    })?;
 
-    for i in 0 .. lines.len() {
-        let line = lines[i];
-        let chars: Vec<char> = line.chars().collect();
+    let height = grid.height() as i32;
+    let width = grid.width() as i32;
 
-        for j in 0 .. line.len() {
-            let c = chars[j];
+    for i in 0 .. grid.height() {
+        for j in 0 .. grid.width() {
+            let c = grid[i][j];
 
             if c == '.' {
                 continue;
@@ -34,7 +36,7 @@ fn parse(input: &str) -> Input {
         }
     }
 
-    (width, height, entries)
+    Ok((width, height, entries))
 }
 
 fn valid(width: &i32, height: &i32, (a, b): &(i32, i32)) -> bool {
@@ -139,7 +141,7 @@ mod tests {
                      ............
                      ............";
 
-        let r = parse(input);
+        let r = parse(input).unwrap();
         assert_eq!(r.0, 12);
         assert_eq!(r.1, 12);
         assert_eq!(r.2.get(&'0').unwrap().len(), 4);
@@ -173,7 +175,7 @@ mod tests {
                      ............
                      ............";
 
-        assert_eq!(part1(&parse(input)), 14);
+        assert_eq!(part1(&parse(input).unwrap()), 14);
     }
 
     #[test]
@@ -191,6 +193,6 @@ mod tests {
                      ............
                      ............";
 
-        assert_eq!(part2(&parse(input)), 34);
+        assert_eq!(part2(&parse(input).unwrap()), 34);
     }
 }
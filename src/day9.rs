@@ -8,17 +8,19 @@ enum Entry {
 
 use Entry::*;
 
+use crate::parse::{self, ParseError};
+
 type Input = Vec<Entry>;
 
 #[aoc_generator(day9)]
-fn parse(input: &str) -> Input {
+fn parse(input: &str) -> Result<Input, ParseError> {
     let mut result = Vec::new();
     let mut block = true;
     let mut id = 0;
-    input
-        .chars()
-        .for_each(|c| {
-            let val = c as i32 - 0x30;
+
+    for line in parse::lines(9, input).filter(|l| !l.is_empty()) {
+        for (col, c) in line.chars() {
+            let val = c.to_digit(10).ok_or_else(|| line.error_at(col, c, "a digit"))?;
 
             if block {
                 for _ in 0..val {
@@ -32,9 +34,10 @@ fn parse(input: &str) -> Input {
             }
             
             block = !block
-        });
+        }
+    }
 
-    result
+    Ok(result)
 }
 
 fn print_state(input: &Input) {
@@ -194,7 +197,7 @@ mod tests {
     #[test]
     fn test_gen() {
         let input = "12345";
-        let result = parse(input);
+        let result = parse(input).unwrap();
         assert_eq!(
             result,
             vec![
@@ -204,7 +207,7 @@ mod tests {
         );
 
         let input = "2333133121414131402";
-        let result = parse(input);
+        let result = parse(input).unwrap();
         assert_eq!(result.len(), 42);
         assert_eq!(result[0], Block(0));
         assert_eq!(result[41], Block(9));
@@ -212,13 +215,13 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let input = parse("2333133121414131402");
+        let input = parse("2333133121414131402").unwrap();
          assert_eq!(part1(&input), 1928);
 
-        let input = parse("929292");
+        let input = parse("929292").unwrap();
         assert_eq!(part1(&input), 495);
 
-        let input = parse("54321");
+        let input = parse("54321").unwrap();
         assert_eq!(part1(&input), 31);
     }
 
@@ -286,7 +289,7 @@ mod tests {
 
     #[test]
     fn test_part2() {
-        let input = parse("2333133121414131402");
+        let input = parse("2333133121414131402").unwrap();
         assert_eq!(part2(&input), 2858);
 
         // let input = parse("929292");
@@ -0,0 +1,243 @@
+#![allow(dead_code)]
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::utils::Grid;
+use crate::viz::{Frame, Style};
+
+pub type Rgb = [u8; 3];
+
+pub const BACKGROUND: Rgb = [24, 24, 32];
+
+// Colours for frames recorded from the viz styles
+pub fn style_color(_c: char, style: Style) -> Rgb {
+    match style {
+        Style::Plain => BACKGROUND,
+        Style::Wall => [70, 90, 160],
+        Style::Object => [220, 180, 40],
+        Style::Path => [200, 50, 50],
+        Style::Agent => [60, 200, 90],
+    }
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+// Collects the states of a simulation as images, each cell drawn as a
+// `scale` x `scale` square, to write them as PNG files or an animated GIF.
+// All the frames share a palette of at most 256 colours.
+pub struct Recorder {
+    scale: usize,
+    size: Option<(usize, usize)>,
+    palette: Vec<Rgb>,
+    frames: Vec<Vec<u8>>,
+}
+
+impl Recorder {
+    pub fn new(scale: usize) -> Self {
+        Self { scale, size: None, palette: Vec::new(), frames: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    fn color_index(&mut self, color: Rgb) -> io::Result<u8> {
+        if let Some(index) = self.palette.iter().position(|c| *c == color) {
+            return Ok(index as u8);
+        }
+        if self.palette.len() == 256 {
+            return Err(invalid(String::from("more than 256 colours")));
+        }
+        self.palette.push(color);
+        Ok((self.palette.len() - 1) as u8)
+    }
+
+    fn push(&mut self, height: usize, width: usize, colors: impl Iterator<Item = Rgb>) -> io::Result<()> {
+        match self.size {
+            None => self.size = Some((height, width)),
+            Some(size) if size != (height, width) => {
+                return Err(invalid(format!("frame of {height}x{width} cells, expected {}x{}", size.0, size.1)));
+            }
+            _ => {}
+        }
+
+        let cells = colors
+            .map(|color| self.color_index(color))
+            .collect::<io::Result<_>>()?;
+        self.frames.push(cells);
+        Ok(())
+    }
+
+    pub fn add_grid<T, F>(&mut self, grid: &Grid<T>, color: F) -> io::Result<()>
+    where
+        F: Fn(&T) -> Rgb,
+    {
+        self.push(grid.height(), grid.width(), grid.iter().map(|(_, value)| color(value)))
+    }
+
+    pub fn add_frame<F>(&mut self, frame: &dyn Frame, color: F) -> io::Result<()>
+    where
+        F: Fn(char, Style) -> Rgb,
+    {
+        let (height, width) = (frame.height(), frame.width());
+        let colors = (0 .. height)
+            .flat_map(|i| (0 .. width).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                let (c, style) = frame.cell(i, j);
+                color(c, style)
+            });
+        self.push(height, width, colors)
+    }
+
+    // Size of the images in pixels
+    fn pixel_size(&self) -> (usize, usize) {
+        let (height, width) = self.size.unwrap_or((0, 0));
+        (height * self.scale, width * self.scale)
+    }
+
+    // Palette index of every pixel of the frame
+    fn pixels(&self, cells: &[u8]) -> Vec<u8> {
+        let (_, width) = self.size.unwrap_or((0, 0));
+        let (pixel_height, pixel_width) = self.pixel_size();
+        let mut result = Vec::with_capacity(pixel_height * pixel_width);
+
+        for y in 0 .. pixel_height {
+            for x in 0 .. pixel_width {
+                result.push(cells[(y / self.scale) * width + x / self.scale]);
+            }
+        }
+        result
+    }
+
+    pub fn write_gif<P: AsRef<Path>>(&self, path: P, delay: Duration) -> io::Result<()> {
+        let (height, width) = self.pixel_size();
+        let height = u16::try_from(height).map_err(|_| invalid(format!("{height} pixels high is too big for a gif")))?;
+        let width = u16::try_from(width).map_err(|_| invalid(format!("{width} pixels wide is too big for a gif")))?;
+
+        let palette: Vec<u8> = self.palette.iter().flatten().copied().collect();
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(file, width, height, &palette).map_err(io::Error::other)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+
+        // GIF delays are in hundredths of second
+        let delay = (delay.as_millis() / 10) as u16;
+        for cells in &self.frames {
+            let mut frame = gif::Frame::from_indexed_pixels(width, height, self.pixels(cells), None);
+            frame.delay = delay;
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    // Writes `<prefix>-0000.png`, `<prefix>-0001.png`... into `dir`
+    pub fn write_pngs<P: AsRef<Path>>(&self, dir: P, prefix: &str) -> io::Result<Vec<PathBuf>> {
+        let (height, width) = self.pixel_size();
+        fs::create_dir_all(&dir)?;
+
+        let mut result = Vec::new();
+        for (n, cells) in self.frames.iter().enumerate() {
+            let path = dir.as_ref().join(format!("{prefix}-{n:04}.png"));
+
+            let mut encoder = png::Encoder::new(BufWriter::new(File::create(&path)?), width as u32, height as u32);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let data: Vec<u8> = self
+                .pixels(cells)
+                .iter()
+                .flat_map(|i| self.palette[*i as usize])
+                .collect();
+
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&data)?;
+            result.push(path);
+        }
+        Ok(result)
+    }
+}
+
+
+#[cfg(test)]
+fn char_color(c: &char) -> Rgb {
+    match c {
+        '#' => [255, 255, 255],
+        _ => [0, 0, 0],
+    }
+}
+
+#[test]
+fn test_recorder_frames() {
+    let mut recorder = Recorder::new(2);
+    recorder.add_grid(&Grid::from_chars("#.\n.#"), char_color).unwrap();
+    recorder.add_grid(&Grid::from_chars(".#\n#."), char_color).unwrap();
+
+    assert_eq!(recorder.len(), 2);
+    assert_eq!(recorder.palette, vec![[255, 255, 255], [0, 0, 0]]);
+    assert_eq!(recorder.pixels(&recorder.frames[0]), vec![
+        0, 0, 1, 1,
+        0, 0, 1, 1,
+        1, 1, 0, 0,
+        1, 1, 0, 0,
+    ]);
+
+    let err = recorder.add_grid(&Grid::from_chars("###"), char_color).unwrap_err();
+    assert_eq!(err.to_string(), "frame of 1x3 cells, expected 2x2");
+}
+
+#[test]
+fn test_palette_limit() {
+    let mut recorder = Recorder::new(1);
+    let grid = Grid::from(vec![(0 ..= 255).collect::<Vec<u8>>()]);
+    recorder.add_grid(&grid, |n| [*n, 0, 0]).unwrap();
+
+    let err = recorder.add_grid(&Grid::from(vec![vec![0u8; 256]]), |_| [0, 0, 1]).unwrap_err();
+    assert_eq!(err.to_string(), "more than 256 colours");
+    assert_eq!(recorder.palette.len(), 256);
+    assert_eq!(recorder.len(), 1);
+}
+
+#[test]
+fn test_write_gif() {
+    let path = std::env::temp_dir().join("aoc-2024-export-test.gif");
+
+    let mut recorder = Recorder::new(3);
+    recorder.add_grid(&Grid::from_chars("#.\n.#"), char_color).unwrap();
+    recorder.add_grid(&Grid::from_chars(".#\n#."), char_color).unwrap();
+    recorder.write_gif(&path, Duration::from_millis(100)).unwrap();
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+    assert_eq!((decoder.width(), decoder.height()), (6, 6));
+
+    let mut frames = 0;
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!(frame.delay, 10);
+        assert_eq!(frame.buffer[0], if frames == 0 { 0 } else { 1 });
+        frames += 1;
+    }
+    assert_eq!(frames, 2);
+}
+
+#[test]
+fn test_write_pngs() {
+    let dir = std::env::temp_dir().join("aoc-2024-export-test");
+
+    let mut recorder = Recorder::new(1);
+    recorder.add_grid(&Grid::from_chars("#.\n.#"), char_color).unwrap();
+    let paths = recorder.write_pngs(&dir, "frame").unwrap();
+    assert_eq!(paths, vec![dir.join("frame-0000.png")]);
+
+    let decoder = png::Decoder::new(io::BufReader::new(File::open(&paths[0]).unwrap()));
+    let mut reader = decoder.read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size().unwrap()];
+    reader.next_frame(&mut data).unwrap();
+    assert_eq!(data, vec![255, 255, 255, 0, 0, 0, 0, 0, 0, 255, 255, 255]);
+}
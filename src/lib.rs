@@ -25,6 +25,15 @@ mod day2;
 mod day1;
 
 mod utils;
+mod parse;
+mod viz;
+mod export;
+mod search;
+pub mod solutions;
+mod answers;
+mod bench;
+mod parallel;
+pub mod cli;
 
 extern crate aoc_runner;
 
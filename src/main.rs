@@ -1,7 +1,8 @@
-// extern crate advent_of_code_2015;
-extern crate aoc_runner_derive;
-extern crate aoc_runner;
+extern crate aoc_2024;
 
-use aoc_runner_derive::aoc_main;
+use std::env;
+use std::process::ExitCode;
 
-aoc_main! { lib = aoc_2024 }
+fn main() -> ExitCode {
+    aoc_2024::cli::main(env::args().skip(1))
+}
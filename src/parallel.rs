@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::panic;
+use std::sync::Once;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+use crate::solutions::{Answer, Solution};
+use crate::viz;
+
+pub struct Outcome {
+    pub day: u32,
+    pub part: u32,
+    pub result: Result<Answer, String>,
+}
+
+impl Outcome {
+    pub fn time(&self) -> Duration {
+        match &self.result {
+            Ok(answer) => answer.generator + answer.runner,
+            Err(_) => Duration::ZERO,
+        }
+    }
+}
+
+// The default hook would print the `todo!()` panics over the other days
+fn silence_quiet_panics() {
+    static HOOK: Once = Once::new();
+
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !viz::is_quiet() {
+                default(info);
+            }
+        }));
+    });
+}
+
+// Runs the current thread quiet until dropped, panics included
+pub struct Quiet {
+    was_quiet: bool,
+}
+
+impl Quiet {
+    pub fn new() -> Self {
+        silence_quiet_panics();
+        let was_quiet = viz::is_quiet();
+        viz::set_quiet(true);
+        Self { was_quiet }
+    }
+}
+
+impl Drop for Quiet {
+    fn drop(&mut self) {
+        viz::set_quiet(self.was_quiet);
+    }
+}
+
+// Runs the solutions on `jobs` threads, each one taking the next pending
+// solution. The solutions run quiet, and the outcomes are returned in the
+// same order as the solutions.
+pub fn run(solutions: &[Solution], inputs: &HashMap<u32, Result<String, String>>, jobs: usize) -> Vec<Outcome> {
+    silence_quiet_panics();
+    let next = AtomicUsize::new(0);
+
+    let mut outcomes: Vec<(usize, Outcome)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0 .. jobs.max(1))
+            .map(|_| scope.spawn(|| {
+                viz::set_quiet(true);
+                let mut done = Vec::new();
+
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(solution) = solutions.get(index) else {
+                        break;
+                    };
+
+                    let result = match inputs.get(&solution.day) {
+                        Some(Ok(text)) => solution.run(text),
+                        Some(Err(e)) => Err(e.clone()),
+                        None => Err(format!("reading: no input for day {}", solution.day)),
+                    };
+                    done.push((index, Outcome { day: solution.day, part: solution.part, result }));
+                }
+                done
+            }))
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    outcomes.sort_by_key(|(index, _)| *index);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+
+#[test]
+fn test_run() {
+    let solutions = vec![
+        Solution::find(1, 1).unwrap(),
+        Solution::find(1, 2).unwrap(),
+        Solution::find(2, 1).unwrap(),
+        Solution::find(25, 2).unwrap(),
+    ];
+    let inputs = HashMap::from([
+        (1, Ok(String::from("3   4\n4   3\n2   5\n1   3\n3   9\n3   3"))),
+        (2, Err(String::from("reading day2.txt: not found"))),
+        (25, Ok(String::from("#####\n.....\n.....\n.....\n.....\n.....\n....."))),
+    ]);
+
+    let outcomes = run(&solutions, &inputs, 3);
+    let results: Vec<_> = outcomes
+        .iter()
+        .map(|o| (o.day, o.part, o.result.as_ref().map(|a| a.value.as_str()).map_err(|e| e.as_str())))
+        .collect();
+
+    assert_eq!(results, vec![
+        (1, 1, Ok("11")),
+        (1, 2, Ok("31")),
+        (2, 1, Err("reading day2.txt: not found")),
+        (25, 2, Err("running: panicked")),
+    ]);
+}
@@ -0,0 +1,175 @@
+#![allow(dead_code)]
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Error returned by the input generators. Lines and columns start at 1 and
+// refer to the raw input, indentation included.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseError {
+    pub day: u32,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn new(day: u32, line: usize, column: usize, expected: &str, found: &str) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            expected: String::from(expected),
+            found: String::from(found),
+        }
+    }
+
+    // The input finished before the generator had everything it needed
+    pub fn eof(day: u32, input: &str, expected: &str) -> Self {
+        Self::new(day, input.lines().count() + 1, 1, expected, "end of input")
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "day {} line {} column {}: expected {}, found ",
+               self.day, self.line, self.column, self.expected)?;
+
+        if self.found == "end of input" || self.found == "end of line" {
+            write!(f, "{}", self.found)
+        } else {
+            write!(f, "`{}`", self.found)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+// A line of the puzzle input that remembers where it came from, so tokens
+// taken from it (trimmed, split...) can be reported with their column.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub day: u32,
+    pub number: usize,
+    pub text: &'a str,
+}
+
+pub fn lines(day: u32, input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(move |(i, text)| Line { day, number: i + 1, text })
+}
+
+impl<'a> Line<'a> {
+    pub fn trim(&self) -> &'a str {
+        self.text.trim()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trim().is_empty()
+    }
+
+    // Column of `token` when it's a slice of this line. Tokens built
+    // elsewhere are reported at the start of the content.
+    pub fn column(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let pos = token.as_ptr() as usize;
+
+        if pos >= start && pos <= start + self.text.len() {
+            self.text[.. pos - start].chars().count() + 1
+        } else {
+            self.text.len() - self.text.trim_start().len() + 1
+        }
+    }
+
+    pub fn error(&self, token: &str, expected: &str) -> ParseError {
+        let found = if token.is_empty() { "end of line" } else { token };
+        ParseError::new(self.day, self.number, self.column(token), expected, found)
+    }
+
+    pub fn error_at(&self, column: usize, found: char, expected: &str) -> ParseError {
+        ParseError::new(self.day, self.number, column, expected, &found.to_string())
+    }
+
+    // Error pointing just after the last character of the line
+    pub fn error_end(&self, expected: &str) -> ParseError {
+        let end = &self.text[self.text.trim_end().len() ..];
+        ParseError::new(self.day, self.number, self.column(end), expected, "end of line")
+    }
+
+    pub fn parse<T: FromStr>(&self, token: &'a str, expected: &str) -> Result<T, ParseError> {
+        token.parse().map_err(|_| self.error(token, expected))
+    }
+
+    // Splits the trimmed line by `pat` requiring exactly `n` parts
+    pub fn split_n(&self, pat: &str, n: usize, expected: &str) -> Result<Vec<&'a str>, ParseError> {
+        let parts: Vec<&'a str> = self.trim().split(pat).collect();
+        if parts.len() == n {
+            Ok(parts)
+        } else if parts.len() < n {
+            Err(self.error_end(expected))
+        } else {
+            let extra = parts[n];
+            Err(self.error(extra, "end of line"))
+        }
+    }
+
+    // Iterates the non-blank characters of the line with their column
+    pub fn chars(&self) -> impl Iterator<Item = (usize, char)> + 'a {
+        let offset = self.column(self.trim());
+        self.trim().chars().enumerate().map(move |(i, c)| (offset + i, c))
+    }
+}
+
+
+#[test]
+fn test_line_column() {
+    let input = "a: 1\n   foo bar";
+    let lines: Vec<_> = lines(3, input).collect();
+
+    assert_eq!(lines[0].number, 1);
+    assert_eq!(lines[1].number, 2);
+
+    let parts: Vec<_> = lines[1].trim().split(' ').collect();
+    assert_eq!(lines[1].column(parts[0]), 4);
+    assert_eq!(lines[1].column(parts[1]), 8);
+
+    let chars: Vec<_> = lines[1].chars().take(2).collect();
+    assert_eq!(chars, vec![(4, 'f'), (5, 'o')]);
+}
+
+#[test]
+fn test_line_parse() {
+    let line = lines(1, "  12 x3").next().unwrap();
+    let parts: Vec<_> = line.trim().split(' ').collect();
+
+    assert_eq!(line.parse::<u32>(parts[0], "a number"), Ok(12));
+    assert_eq!(
+        line.parse::<u32>(parts[1], "a number"),
+        Err(ParseError::new(1, 1, 6, "a number", "x3"))
+    );
+}
+
+#[test]
+fn test_split_n() {
+    let line = lines(5, "47|53").next().unwrap();
+    assert_eq!(line.split_n("|", 2, "`|`"), Ok(vec!["47", "53"]));
+    assert_eq!(line.split_n(",", 2, "`,`"), Err(ParseError::new(5, 1, 6, "`,`", "end of line")));
+
+    let line = lines(5, "47|53|1").next().unwrap();
+    assert_eq!(line.split_n("|", 2, "`|`"), Err(ParseError::new(5, 1, 7, "end of line", "1")));
+}
+
+#[test]
+fn test_display() {
+    let err = ParseError::new(15, 3, 7, "one of `<>^v`", "x");
+    assert_eq!(err.to_string(), "day 15 line 3 column 7: expected one of `<>^v`, found `x`");
+
+    let err = ParseError::eof(17, "Register A: 1\nRegister B: 2", "`Register C: <number>`");
+    assert_eq!(err.to_string(), "day 17 line 3 column 1: expected `Register C: <number>`, found end of input");
+
+    let err = ParseError::new(1, 2, 4, "a number", "end of line");
+    assert_eq!(err.to_string(), "day 1 line 2 column 4: expected a number, found end of line");
+}
@@ -0,0 +1,317 @@
+#![allow(dead_code)]
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+// Entry of the open set. The ordering is reversed and only looks at the
+// priority, so the BinaryHeap pops the cheapest state first and the states
+// don't need to implement Ord.
+struct Open<S> {
+    priority: u32,
+    state: S,
+}
+
+impl<S> PartialEq for Open<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S> Eq for Open<S> {}
+
+impl<S> PartialOrd for Open<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for Open<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+// Cost to reach every visited state and the state it was reached from
+pub struct Paths<S> {
+    pub cost: HashMap<S, u32>,
+    pub from: HashMap<S, S>,
+}
+
+impl<S: Hash + Eq + Clone> Paths<S> {
+    pub fn cost(&self, state: &S) -> Option<u32> {
+        self.cost.get(state).copied()
+    }
+
+    // States from the start to `end`, both included
+    pub fn path(&self, end: &S) -> Option<Vec<S>> {
+        if !self.cost.contains_key(end) {
+            return None;
+        }
+
+        let mut result = vec![end.clone()];
+        let mut current = end;
+
+        while let Some(prev) = self.from.get(current) {
+            result.push(prev.clone());
+            current = prev;
+        }
+        result.reverse();
+        Some(result)
+    }
+}
+
+// Same as `Paths` but keeping every predecessor that reaches a state with
+// its minimum cost, so all the shortest paths can be recovered.
+pub struct AllPaths<S> {
+    pub cost: HashMap<S, u32>,
+    pub from: HashMap<S, Vec<S>>,
+}
+
+impl<S: Hash + Eq + Clone> AllPaths<S> {
+    pub fn cost(&self, state: &S) -> Option<u32> {
+        self.cost.get(state).copied()
+    }
+
+    // One of the shortest paths to `end`
+    pub fn path(&self, end: &S) -> Option<Vec<S>> {
+        if !self.cost.contains_key(end) {
+            return None;
+        }
+
+        let mut result = vec![end.clone()];
+        let mut current = end;
+
+        while let Some(prev) = self.from.get(current).and_then(|p| p.first()) {
+            result.push(prev.clone());
+            current = prev;
+        }
+        result.reverse();
+        Some(result)
+    }
+
+    // Every state that belongs to a shortest path to any of `ends`
+    pub fn nodes(&self, ends: &[S]) -> HashSet<S> {
+        let mut result = HashSet::new();
+        let mut pending: Vec<S> = ends
+            .iter()
+            .filter(|e| self.cost.contains_key(e))
+            .cloned()
+            .collect();
+
+        while let Some(current) = pending.pop() {
+            if !result.insert(current.clone()) {
+                continue;
+            }
+            if let Some(prev) = self.from.get(&current) {
+                pending.extend(prev.iter().cloned());
+            }
+        }
+        result
+    }
+}
+
+// Explores every state reachable from `start`. `neighbours` returns the
+// next states with the cost of the step.
+pub fn dijkstra<S, F, I>(start: S, mut neighbours: F) -> Paths<S>
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, u32)>,
+{
+    let mut cost = HashMap::from([(start.clone(), 0)]);
+    let mut from = HashMap::new();
+    let mut open = BinaryHeap::from([Open { priority: 0, state: start }]);
+
+    while let Some(Open { priority, state }) = open.pop() {
+        // Already reached with a lower cost after being pushed
+        if priority > cost[&state] {
+            continue;
+        }
+
+        for (next, step) in neighbours(&state) {
+            let tentative = priority + step;
+
+            if cost.get(&next).is_none_or(|&c| tentative < c) {
+                cost.insert(next.clone(), tentative);
+                from.insert(next.clone(), state.clone());
+                open.push(Open { priority: tentative, state: next });
+            }
+        }
+    }
+
+    Paths { cost, from }
+}
+
+// Dijkstra keeping all the predecessors with the same minimum cost
+pub fn dijkstra_all<S, F, I>(start: S, mut neighbours: F) -> AllPaths<S>
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, u32)>,
+{
+    let mut cost = HashMap::from([(start.clone(), 0)]);
+    let mut from: HashMap<S, Vec<S>> = HashMap::new();
+    let mut open = BinaryHeap::from([Open { priority: 0, state: start }]);
+
+    while let Some(Open { priority, state }) = open.pop() {
+        if priority > cost[&state] {
+            continue;
+        }
+
+        for (next, step) in neighbours(&state) {
+            let tentative = priority + step;
+
+            match cost.get(&next) {
+                Some(&c) if tentative > c => {}
+                Some(&c) if tentative == c => {
+                    from.entry(next).or_default().push(state.clone());
+                }
+                _ => {
+                    cost.insert(next.clone(), tentative);
+                    from.insert(next.clone(), vec![state.clone()]);
+                    open.push(Open { priority: tentative, state: next });
+                }
+            }
+        }
+    }
+
+    AllPaths { cost, from }
+}
+
+// Shortest path from `start` to the first state accepted by `is_goal`.
+// `heuristic` must never overestimate the remaining cost.
+pub fn astar<S, F, I, H, G>(start: S, mut neighbours: F, heuristic: H, is_goal: G) -> Option<(Vec<S>, u32)>
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, u32)>,
+    H: Fn(&S) -> u32,
+    G: Fn(&S) -> bool,
+{
+    let mut paths = Paths { cost: HashMap::from([(start.clone(), 0)]), from: HashMap::new() };
+    let mut open = BinaryHeap::from([Open { priority: heuristic(&start), state: start }]);
+
+    while let Some(Open { priority, state }) = open.pop() {
+        let current = paths.cost[&state];
+        if priority > current + heuristic(&state) {
+            continue;
+        }
+
+        if is_goal(&state) {
+            return paths.path(&state).map(|p| (p, current));
+        }
+
+        for (next, step) in neighbours(&state) {
+            let tentative = current + step;
+
+            if paths.cost.get(&next).is_none_or(|&c| tentative < c) {
+                paths.cost.insert(next.clone(), tentative);
+                paths.from.insert(next.clone(), state.clone());
+                open.push(Open { priority: tentative + heuristic(&next), state: next });
+            }
+        }
+    }
+    None
+}
+
+// Breadth first search where every step costs 1
+pub fn bfs<S, F, I>(start: S, mut neighbours: F) -> Paths<S>
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+{
+    let mut cost = HashMap::from([(start.clone(), 0)]);
+    let mut from = HashMap::new();
+    let mut pending = VecDeque::from([start]);
+
+    while let Some(state) = pending.pop_front() {
+        let current = cost[&state];
+
+        for next in neighbours(&state) {
+            if !cost.contains_key(&next) {
+                cost.insert(next.clone(), current + 1);
+                from.insert(next.clone(), state.clone());
+                pending.push_back(next);
+            }
+        }
+    }
+
+    Paths { cost, from }
+}
+
+
+#[cfg(test)]
+fn grid_neighbours(map: &[&str], (i, j): (usize, usize)) -> Vec<(usize, usize)> {
+    let height = map.len() as i32;
+    let width = map[0].len() as i32;
+
+    [(-1, 0), (0, 1), (1, 0), (0, -1)]
+        .iter()
+        .map(|(di, dj)| (i as i32 + di, j as i32 + dj))
+        .filter(|&(i, j)| i >= 0 && i < height && j >= 0 && j < width)
+        .map(|(i, j)| (i as usize, j as usize))
+        .filter(|&(i, j)| map[i].as_bytes()[j] != b'#')
+        .collect()
+}
+
+#[test]
+fn test_dijkstra() {
+    // a -1-> b -1-> c and a -5-> c
+    let edges = HashMap::from([
+        ('a', vec![('b', 1), ('c', 5)]),
+        ('b', vec![('c', 1)]),
+    ]);
+    let paths = dijkstra('a', |s| edges.get(s).cloned().unwrap_or_default());
+
+    assert_eq!(paths.cost(&'c'), Some(2));
+    assert_eq!(paths.path(&'c'), Some(vec!['a', 'b', 'c']));
+    assert_eq!(paths.path(&'d'), None);
+}
+
+#[test]
+fn test_dijkstra_all() {
+    // Two paths of cost 2 from a to d
+    let edges = HashMap::from([
+        ('a', vec![('b', 1), ('c', 1), ('e', 3)]),
+        ('b', vec![('d', 1)]),
+        ('c', vec![('d', 1)]),
+        ('e', vec![('d', 1)]),
+    ]);
+    let paths = dijkstra_all('a', |s| edges.get(s).cloned().unwrap_or_default());
+
+    assert_eq!(paths.cost(&'d'), Some(2));
+    assert_eq!(paths.path(&'d').map(|p| p.len()), Some(3));
+    assert_eq!(paths.nodes(&['d']), HashSet::from(['a', 'b', 'c', 'd']));
+}
+
+#[test]
+fn test_bfs_and_astar() {
+    let map = [
+        "..#....",
+        "..#.##.",
+        "....#..",
+        "###.#.#",
+        "......."];
+    let end = (0, 6);
+
+    let paths = bfs((0, 0), |&p| grid_neighbours(&map, p));
+    assert_eq!(paths.cost(&end), Some(10));
+    assert_eq!(paths.cost(&(0, 2)), None);
+
+    let manhattan = |&(i, j): &(usize, usize)| (i.abs_diff(end.0) + j.abs_diff(end.1)) as u32;
+    let (path, cost) = astar(
+        (0, 0),
+        |&p| grid_neighbours(&map, p).into_iter().map(|n| (n, 1)),
+        manhattan,
+        |&p| p == end,
+    ).unwrap();
+
+    assert_eq!(cost, 10);
+    assert_eq!(path.len(), 11);
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert_eq!(path.last(), Some(&end));
+
+    let blocked = astar((0, 0), |_| Vec::new(), manhattan, |&p| p == end);
+    assert_eq!(blocked, None);
+}
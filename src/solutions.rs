@@ -0,0 +1,149 @@
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+use aoc_runner::{ArcStr, Runner};
+use crate::*;
+
+type Build = fn(ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+// A day/part registered with `#[aoc]`. `build` is the factory generated by
+// aoc-runner, it runs the generator and returns the runner for the part.
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    build: Build,
+}
+
+pub struct Answer {
+    pub day: u32,
+    pub part: u32,
+    pub value: String,
+    pub generator: Duration,
+    pub runner: Duration,
+}
+
+impl Solution {
+    fn new(day: u32, part: u32, build: Build) -> Self {
+        Self { day, part, build }
+    }
+
+    pub fn find(day: u32, part: u32) -> Option<Solution> {
+        all().into_iter().find(|s| s.day == day && s.part == part)
+    }
+
+    pub fn run(&self, input: &str) -> Result<Answer, String> {
+        let start = Instant::now();
+        let runner = (self.build)(ArcStr::from(input))
+            .map_err(|e| format!("generating: {e}"))?;
+
+        // Some parts are still `todo!()`, they shouldn't stop the other days
+        let inter = Instant::now();
+        let value = panic::catch_unwind(AssertUnwindSafe(|| runner.try_run()))
+            .map_err(|_| String::from("running: panicked"))?
+            .map_err(|e| format!("running: {e}"))?;
+
+        Ok(Answer {
+            day: self.day,
+            part: self.part,
+            value: value.to_string(),
+            generator: inter - start,
+            runner: inter.elapsed(),
+        })
+    }
+}
+
+pub fn all() -> Vec<Solution> {
+    vec![
+        Solution::new(1, 1, Factory::day1_part1),
+        Solution::new(1, 2, Factory::day1_part2),
+        Solution::new(2, 1, Factory::day2_part1),
+        Solution::new(2, 2, Factory::day2_part2),
+        Solution::new(3, 1, Factory::day3_part1),
+        Solution::new(3, 2, Factory::day3_part2),
+        Solution::new(4, 1, Factory::day4_part1),
+        Solution::new(4, 2, Factory::day4_part2),
+        Solution::new(5, 1, Factory::day5_part1),
+        Solution::new(5, 2, Factory::day5_part2),
+        Solution::new(6, 1, Factory::day6_part1),
+        Solution::new(6, 2, Factory::day6_part2),
+        Solution::new(7, 1, Factory::day7_part1),
+        Solution::new(7, 2, Factory::day7_part2),
+        Solution::new(8, 1, Factory::day8_part1),
+        Solution::new(8, 2, Factory::day8_part2),
+        Solution::new(9, 1, Factory::day9_part1),
+        Solution::new(9, 2, Factory::day9_part2),
+        Solution::new(10, 1, Factory::day10_part1),
+        Solution::new(10, 2, Factory::day10_part2),
+        Solution::new(11, 1, Factory::day11_part1),
+        Solution::new(11, 2, Factory::day11_part2),
+        Solution::new(12, 1, Factory::day12_part1),
+        Solution::new(12, 2, Factory::day12_part2),
+        Solution::new(13, 1, Factory::day13_part1),
+        Solution::new(13, 2, Factory::day13_part2),
+        Solution::new(14, 1, Factory::day14_part1),
+        Solution::new(14, 2, Factory::day14_part2),
+        Solution::new(15, 1, Factory::day15_part1),
+        Solution::new(15, 2, Factory::day15_part2),
+        Solution::new(16, 1, Factory::day16_part1),
+        Solution::new(16, 2, Factory::day16_part2),
+        Solution::new(17, 1, Factory::day17_part1),
+        Solution::new(17, 2, Factory::day17_part2),
+        Solution::new(18, 1, Factory::day18_part1),
+        Solution::new(18, 2, Factory::day18_part2),
+        Solution::new(19, 1, Factory::day19_part1),
+        Solution::new(19, 2, Factory::day19_part2),
+        Solution::new(20, 1, Factory::day20_part1),
+        Solution::new(20, 2, Factory::day20_part2),
+        Solution::new(21, 1, Factory::day21_part1),
+        Solution::new(21, 2, Factory::day21_part2),
+        Solution::new(22, 1, Factory::day22_part1),
+        Solution::new(22, 2, Factory::day22_part2),
+        Solution::new(23, 1, Factory::day23_part1),
+        Solution::new(23, 2, Factory::day23_part2),
+        Solution::new(24, 1, Factory::day24_part1),
+        Solution::new(24, 2, Factory::day24_part2),
+        Solution::new(25, 1, Factory::day25_part1),
+        Solution::new(25, 2, Factory::day25_part2),
+    ]
+}
+
+pub fn input_path(day: u32) -> String {
+    format!("input/2024/day{day}.txt")
+}
+
+// Reads the puzzle input, `-` reads it from stdin
+pub fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("reading stdin: {e}"))?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("reading {path}: {e}"))
+    }
+}
+
+
+#[test]
+fn test_find() {
+    assert_eq!(all().len(), 50);
+    assert!(Solution::find(25, 2).is_some());
+    assert!(Solution::find(26, 1).is_none());
+    assert!(Solution::find(1, 3).is_none());
+}
+
+#[test]
+fn test_run() {
+    let solution = Solution::find(1, 1).unwrap();
+
+    let answer = solution.run("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n").unwrap();
+    assert_eq!(answer.day, 1);
+    assert_eq!(answer.part, 1);
+    assert_eq!(answer.value, "11");
+
+    let err = solution.run("3   4\n4   x").err().unwrap();
+    assert_eq!(err, "generating: day 1 line 2 column 5: expected a number, found `x`");
+}
@@ -1,36 +1,235 @@
 #![allow(dead_code)]
-use std::convert::TryFrom;
-
-// Safely access the array of inputs
-pub fn get2d(input: &Vec<Vec<char>>, i: i32, j: i32) -> &char {
-    if let Ok(i) = usize::try_from(i) {
-        if let Some(v2) = input.get(i) {
-            if let Ok(j) = usize::try_from(j) {
-                v2.get(j).unwrap_or(&' ')
-            } else {
-                &' '
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use crate::parse::{self, ParseError};
+
+const DIRS4: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+const DIRS8: [(i32, i32); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+
+// Rectangular map stored row by row. Positions are (row, column) like
+// the rest of the puzzles; indexing with a single usize returns the row so
+// `grid[i][j]` keeps working as with the old nested vectors.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self { width, height, cells: vec![value; width * height] }
+    }
+}
+
+impl<T> Grid<T> {
+    // Builds the grid from the puzzle text, each line is trimmed so the
+    // indented inputs in the tests can be used directly.
+    pub fn parse<F>(input: &str, f: F) -> Self
+    where
+        F: Fn(char) -> T,
+    {
+        let rows: Vec<Vec<T>> = input
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| l.chars().map(&f).collect())
+            .collect();
+        Self::from(rows)
+    }
+
+    // Same as `parse` but reporting unknown cells and ragged rows. `expected`
+    // describes the valid cells for the error message.
+    pub fn try_parse<F>(day: u32, input: &str, expected: &str, f: F) -> Result<Self, ParseError>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let mut rows: Vec<Vec<T>> = Vec::new();
+
+        for line in parse::lines(day, input).filter(|l| !l.is_empty()) {
+            let mut row = Vec::new();
+            for (col, c) in line.chars() {
+                match f(c) {
+                    Some(v) => row.push(v),
+                    None => return Err(line.error_at(col, c, expected)),
+                }
+                if rows.first().is_some_and(|r| row.len() > r.len()) {
+                    return Err(line.error_at(col, c, "end of row"));
+                }
+            }
+
+            if let Some(first) = rows.first() {
+                if row.len() < first.len() {
+                    return Err(line.error_end(&format!("row of {} cells", first.len())));
+                }
             }
+            rows.push(row);
+        }
+
+        if rows.is_empty() {
+            return Err(ParseError::eof(day, input, expected));
+        }
+        Ok(Self::from(rows))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, i: i32, j: i32) -> bool {
+        i >= 0 && j >= 0 && (i as usize) < self.height && (j as usize) < self.width
+    }
+
+    // Safely access the grid, out of bounds positions return None
+    pub fn get(&self, i: i32, j: i32) -> Option<&T> {
+        if self.contains(i, j) {
+            Some(&self.cells[i as usize * self.width + j as usize])
         } else {
-            &' '
+            None
         }
-    } else {
-        &' '
+    }
+
+    pub fn get_mut(&mut self, i: i32, j: i32) -> Option<&mut T> {
+        if self.contains(i, j) {
+            Some(&mut self.cells[i as usize * self.width + j as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_or<'a>(&'a self, i: i32, j: i32, default: &'a T) -> &'a T {
+        self.get(i, j).unwrap_or(default)
+    }
+
+    pub fn set(&mut self, (i, j): (usize, usize), value: T) {
+        self[i][j] = value;
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0 .. self.height).flat_map(move |i| (0 .. width).map(move |j| (i, j)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn row(&self, i: usize) -> impl Iterator<Item = &T> {
+        self[i].iter()
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &T> {
+        (0 .. self.height).map(move |i| &self[i][j])
+    }
+
+    // Cells from `start` moving by `dir` until leaving the grid
+    pub fn ray(&self, (i, j): (usize, usize), (di, dj): (i32, i32)) -> impl Iterator<Item = &T> {
+        let (i, j) = (i as i32, j as i32);
+        (0 ..)
+            .map(move |n| self.get(i + n * di, j + n * dj))
+            .take_while(|c| c.is_some())
+            .flatten()
+    }
+
+    pub fn diagonal(&self, start: (usize, usize)) -> impl Iterator<Item = &T> {
+        self.ray(start, (1, 1))
+    }
+
+    pub fn anti_diagonal(&self, start: (usize, usize)) -> impl Iterator<Item = &T> {
+        self.ray(start, (1, -1))
+    }
+
+    fn offsets<'a>(&self, (i, j): (usize, usize), dirs: &'a [(i32, i32)]) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let (height, width) = (self.height as i32, self.width as i32);
+        let (i, j) = (i as i32, j as i32);
+        dirs.iter()
+            .map(move |(di, dj)| (i + di, j + dj))
+            .filter(move |(i, j)| *i >= 0 && *j >= 0 && *i < height && *j < width)
+            .map(|(i, j)| (i as usize, j as usize))
+    }
+
+    // Orthogonal neighbours inside the grid (N, E, S, W)
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets(pos, &DIRS4)
+    }
+
+    // Orthogonal and diagonal neighbours inside the grid, clockwise from N
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets(pos, &DIRS8)
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    pub fn find(&self, value: &T) -> Option<(usize, usize)> {
+        self.iter().find(|(_, c)| *c == value).map(|(p, _)| p)
+    }
+
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.iter().filter(move |(_, c)| *c == value).map(|(p, _)| p)
+    }
+}
+
+impl Grid<char> {
+    pub fn from_chars(input: &str) -> Self {
+        Self::parse(input, |c| c)
     }
 }
 
-pub fn get_m<'a>(input: &'a Vec<Vec<u32>>, i: i32, j: i32, default: &'a u32) -> &'a u32 {
-    if let Ok(i) = usize::try_from(i) {
-        if let Some(v2) = input.get(i) {
-            if let Ok(j) = usize::try_from(j) {
-                v2.get(j).unwrap_or(default)
-            } else {
-                default
+impl<T> From<Vec<Vec<T>>> for Grid<T> {
+    fn from(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        assert!(rows.iter().all(|r| r.len() == width), "Grid rows must have the same length");
+        let cells = rows.into_iter().flatten().collect();
+        Self { width, height, cells }
+    }
+}
+
+impl<T> Index<usize> for Grid<T> {
+    type Output = [T];
+
+    fn index(&self, i: usize) -> &[T] {
+        &self.cells[i * self.width .. (i + 1) * self.width]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, i: usize) -> &mut [T] {
+        &mut self.cells[i * self.width .. (i + 1) * self.width]
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        &self[i][j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        &mut self[i][j]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for c in row {
+                write!(f, "{c}")?;
             }
-        } else {
-            default
+            writeln!(f)?;
         }
-    } else {
-        default
+        Ok(())
     }
 }
 
@@ -42,28 +241,147 @@ pub fn to_bin(x: u64, d: u32) -> String {
             .map (|n| n.to_string()))
 }
 
+// Small seeded generator (xorshift64*) for the randomized tests and the
+// generated inputs, the same seed always gives the same sequence
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // The state can't be 0
+        Self { state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Uniform enough in `0 .. n` for small `n`
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+}
+
 
 #[test]
 fn test_get() {
-    let input =
-        vec![
-            vec!['S', 'M', 'X', 'X', 'S', 'M'],
-            vec!['S', 'S', 'A', 'M', 'X', 'M'],
-            vec!['S', 'A', 'X', 'M', 'A', 'A'],
-            vec!['X', 'M', 'A', 'S', 'M', 'S'],
-            vec!['X', 'X', 'S', 'A', 'M', 'S'],
-        ];
-
-    assert_eq!(get2d(&input, 0, 0), &'S');
-    assert_eq!(get2d(&input, -1, 0), &' ');
-    assert_eq!(get2d(&input, 19, 0), &' ');
-    assert_eq!(get2d(&input, 0, 1), &'M');
-    assert_eq!(get2d(&input, 0, 2), &'X');
-    assert_eq!(get2d(&input, 0, -1), &' ');
-    assert_eq!(get2d(&input, 0, 20), &' ');
+    let input = Grid::from_chars(
+        "SMXXSM
+         SSAMXM
+         SAXMAA
+         XMASMS
+         XXSAMS");
+
+    assert_eq!(input.get_or(0, 0, &' '), &'S');
+    assert_eq!(input.get_or(-1, 0, &' '), &' ');
+    assert_eq!(input.get_or(19, 0, &' '), &' ');
+    assert_eq!(input.get_or(0, 1, &' '), &'M');
+    assert_eq!(input.get_or(0, 2, &' '), &'X');
+    assert_eq!(input.get_or(0, -1, &' '), &' ');
+    assert_eq!(input.get_or(0, 20, &' '), &' ');
+    assert_eq!(input.get(4, 5), Some(&'S'));
+    assert_eq!(input.get(5, 5), None);
+}
+
+#[test]
+fn test_grid_parse() {
+    let input = Grid::parse("8901\n7.12", |c| c.to_digit(10).unwrap_or(99));
+    assert_eq!(input.width(), 4);
+    assert_eq!(input.height(), 2);
+    assert_eq!(input, Grid::from(vec![vec![8, 9, 0, 1], vec![7, 99, 1, 2]]));
+    assert_eq!(input[1][1], 99);
+    assert_eq!(input[(0, 3)], 1);
+}
+
+#[test]
+fn test_grid_try_parse() {
+    let cell = |c| match c {
+        '#' | '.' => Some(c),
+        _ => None,
+    };
+
+    let input = Grid::try_parse(6, "#.#\n...", "`#` or `.`", cell);
+    assert_eq!(input, Ok(Grid::from(vec![vec!['#', '.', '#'], vec!['.', '.', '.']])));
+
+    let err = Grid::try_parse(6, "#.#\n .x.", "`#` or `.`", cell).unwrap_err();
+    assert_eq!(err, ParseError::new(6, 2, 3, "`#` or `.`", "x"));
+
+    let err = Grid::try_parse(6, "#.#\n..", "`#` or `.`", cell).unwrap_err();
+    assert_eq!(err, ParseError::new(6, 2, 3, "row of 3 cells", "end of line"));
+
+    let err = Grid::try_parse(6, "#.#\n....", "`#` or `.`", cell).unwrap_err();
+    assert_eq!(err, ParseError::new(6, 2, 4, "end of row", "."));
+
+    let err = Grid::try_parse(6, "", "`#` or `.`", cell).unwrap_err();
+    assert_eq!(err, ParseError::new(6, 1, 1, "`#` or `.`", "end of input"));
+}
+
+#[test]
+fn test_grid_neighbours() {
+    let input = Grid::new(3, 3, '.');
+
+    assert_eq!(input.neighbours4((0, 0)).collect::<Vec<_>>(), vec![(0, 1), (1, 0)]);
+    assert_eq!(input.neighbours4((1, 1)).collect::<Vec<_>>(), vec![(0, 1), (1, 2), (2, 1), (1, 0)]);
+    assert_eq!(input.neighbours8((1, 1)).count(), 8);
+    assert_eq!(input.neighbours8((2, 2)).collect::<Vec<_>>(), vec![(1, 2), (2, 1), (1, 1)]);
+}
+
+#[test]
+fn test_grid_find() {
+    let input = Grid::from_chars(
+        "#S.
+         .#.
+         .#E");
+
+    assert_eq!(input.find(&'S'), Some((0, 1)));
+    assert_eq!(input.find(&'E'), Some((2, 2)));
+    assert_eq!(input.find(&'X'), None);
+    assert_eq!(input.find_all(&'#').collect::<Vec<_>>(), vec![(0, 0), (1, 1), (2, 1)]);
+}
+
+#[test]
+fn test_grid_lines() {
+    let input = Grid::from_chars(
+        "ABC
+         DEF
+         GHI");
+
+    assert_eq!(String::from_iter(input.row(1)), "DEF");
+    assert_eq!(String::from_iter(input.column(2)), "CFI");
+    assert_eq!(String::from_iter(input.diagonal((0, 0))), "AEI");
+    assert_eq!(String::from_iter(input.anti_diagonal((0, 2))), "CEG");
+    assert_eq!(String::from_iter(input.ray((2, 2), (-1, 0))), "IFC");
+    assert_eq!(input.rows().count(), 3);
+}
+
+#[test]
+fn test_grid_display() {
+    let mut input = Grid::new(3, 2, '.');
+    input.set((1, 2), '#');
+    assert_eq!(input.to_string(), "...\n..#\n");
 }
 
 #[test]
 fn test_to_bin() {
     assert_eq!(to_bin(15, 5), "01111");
 }
+
+#[test]
+fn test_rng() {
+    let mut a = Rng::new(7);
+    let mut b = Rng::new(7);
+    let values: Vec<_> = (0 .. 100).map(|_| a.below(10)).collect();
+
+    assert_eq!(values, (0 .. 100).map(|_| b.below(10)).collect::<Vec<_>>());
+    assert!(values.iter().all(|v| *v < 10));
+    assert_ne!(Rng::new(8).next_u64(), Rng::new(7).next_u64());
+}
@@ -0,0 +1,443 @@
+#![allow(dead_code)]
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use colored::{Color, Colorize};
+use crate::utils::Grid;
+
+// What a cell represents, the theme decides how it's painted
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    Plain,
+    Wall,
+    Object,
+    Path,
+    Agent,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Theme {
+    pub wall: Option<Color>,
+    pub object: Option<Color>,
+    pub path: Option<Color>,
+    pub agent: Option<Color>,
+}
+
+impl Theme {
+    // No colours, used when writing frames to a file
+    pub const PLAIN: Theme = Theme { wall: None, object: None, path: None, agent: None };
+
+    pub const DEFAULT: Theme = Theme {
+        wall: Some(Color::Blue),
+        object: Some(Color::Yellow),
+        path: Some(Color::Red),
+        agent: Some(Color::Green),
+    };
+
+    pub const HIGH_CONTRAST: Theme = Theme {
+        wall: Some(Color::BrightWhite),
+        object: Some(Color::BrightCyan),
+        path: Some(Color::BrightMagenta),
+        agent: Some(Color::BrightYellow),
+    };
+
+    pub fn paint(&self, c: char, style: Style) -> String {
+        let color = match style {
+            Style::Plain => None,
+            Style::Wall => self.wall,
+            Style::Object => self.object,
+            Style::Path => self.path,
+            Style::Agent => self.agent,
+        };
+
+        match color {
+            Some(color) => c.to_string().color(color).to_string(),
+            None => c.to_string(),
+        }
+    }
+}
+
+// Something that can be drawn as a rectangle of characters
+pub trait Frame {
+    fn height(&self) -> usize;
+    fn width(&self) -> usize;
+    fn cell(&self, i: usize, j: usize) -> (char, Style);
+
+    // Line shown above the frame
+    fn caption(&self) -> Option<String> {
+        None
+    }
+}
+
+// Cell function that doesn't capture anything
+pub type CellFn<T> = fn((usize, usize), &T) -> (char, Style);
+
+// Frame drawing each cell of a grid with `cell`, which also gets the
+// position so the callers can overlay paths or visited sets.
+pub struct GridFrame<'a, T, F> {
+    grid: &'a Grid<T>,
+    cell: F,
+    caption: Option<String>,
+}
+
+impl<'a, T, F> GridFrame<'a, T, F>
+where
+    F: Fn((usize, usize), &T) -> (char, Style),
+{
+    pub fn new(grid: &'a Grid<T>, cell: F) -> Self {
+        Self { grid, cell, caption: None }
+    }
+
+    pub fn caption(mut self, caption: String) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+}
+
+impl<T, F> Frame for GridFrame<'_, T, F>
+where
+    F: Fn((usize, usize), &T) -> (char, Style),
+{
+    fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    fn cell(&self, i: usize, j: usize) -> (char, Style) {
+        (self.cell)((i, j), &self.grid[(i, j)])
+    }
+
+    fn caption(&self) -> Option<String> {
+        self.caption.clone()
+    }
+}
+
+pub fn render(frame: &dyn Frame, theme: &Theme) -> String {
+    let mut result = String::new();
+
+    if let Some(caption) = frame.caption() {
+        result.push_str(&caption);
+        result.push('\n');
+    }
+
+    for i in 0 .. frame.height() {
+        for j in 0 .. frame.width() {
+            let (c, style) = frame.cell(i, j);
+            result.push_str(&theme.paint(c, style));
+        }
+        result.push('\n');
+    }
+    result
+}
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+// Silences `print` and `debug!` on this thread, used when running the
+// solutions in parallel so their output doesn't get mixed
+pub fn set_quiet(quiet: bool) {
+    QUIET.with(|q| q.set(quiet));
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.with(|q| q.get())
+}
+
+// println! for the traces left in the solutions
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if !$crate::viz::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use debug;
+
+// Prints a single frame to stdout
+pub fn print(frame: &dyn Frame) {
+    if !is_quiet() {
+        print!("{}", render(frame, &Theme::DEFAULT));
+    }
+}
+
+// Lines typed in the terminal. Read in a thread so the animation can wait
+// for them with a timeout.
+fn controls() -> &'static Mutex<Receiver<String>> {
+    static CONTROLS: OnceLock<Mutex<Receiver<String>>> = OnceLock::new();
+
+    CONTROLS.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines().map_while(Result::ok) {
+                if tx.send(line.trim().to_string()).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(rx)
+    })
+}
+
+// Shows a sequence of frames. On the terminal each frame replaces the
+// previous one; Enter pauses, and while paused Enter steps to the next
+// frame, `c` continues and `q` stops the animation.
+pub struct Player {
+    out: Box<dyn Write>,
+    theme: Theme,
+    delay: Duration,
+    clear: bool,
+    interactive: bool,
+    paused: bool,
+    frames: usize,
+}
+
+impl Player {
+    pub fn terminal() -> Self {
+        Self {
+            out: Box::new(io::stdout()),
+            theme: Theme::DEFAULT,
+            delay: Duration::from_millis(250),
+            clear: true,
+            interactive: io::stdin().is_terminal(),
+            paused: false,
+            frames: 0,
+        }
+    }
+
+    // Writes the frames one after another without colours or delays
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::to_writer(Box::new(BufWriter::new(File::create(path)?))))
+    }
+
+    pub fn to_writer(out: Box<dyn Write>) -> Self {
+        Self {
+            out,
+            theme: Theme::PLAIN,
+            delay: Duration::ZERO,
+            clear: false,
+            interactive: false,
+            paused: false,
+            frames: 0,
+        }
+    }
+
+    pub fn fps(mut self, fps: f32) -> Self {
+        self.delay = Duration::from_secs_f32(1.0 / fps);
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    // Shows each frame right away, for when the caller waits for the user
+    pub fn no_wait(mut self) -> Self {
+        self.interactive = false;
+        self.delay = Duration::ZERO;
+        self
+    }
+
+    // Starts paused, stepping frame by frame
+    pub fn step(mut self) -> Self {
+        self.paused = true;
+        self
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    // Returns false when the user asked to stop
+    pub fn show(&mut self, frame: &dyn Frame) -> io::Result<bool> {
+        if self.clear {
+            write!(self.out, "\x1Bc")?;
+        } else if self.frames > 0 {
+            writeln!(self.out)?;
+        }
+
+        write!(self.out, "{}", render(frame, &self.theme))?;
+        self.out.flush()?;
+        self.frames += 1;
+
+        Ok(self.wait())
+    }
+
+    fn wait(&mut self) -> bool {
+        if !self.interactive {
+            thread::sleep(self.delay);
+            return true;
+        }
+
+        let input = controls().lock().unwrap();
+        if self.paused {
+            match input.recv().as_deref() {
+                Ok("q") | Err(_) => false,
+                Ok("c") => {
+                    self.paused = false;
+                    true
+                }
+                Ok(_) => true,
+            }
+        } else {
+            match input.recv_timeout(self.delay) {
+                Ok(line) if line == "q" => false,
+                Ok(_) => {
+                    self.paused = true;
+                    true
+                }
+                Err(RecvTimeoutError::Timeout) => true,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.interactive = false;
+                    true
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Esc,
+    Char(char),
+}
+
+// Keys pressed, arrows arrive as `ESC [ A` to `ESC [ D`. An ESC not
+// followed by `[` is the Esc key itself, known only when the next key
+// arrives. Other escape sequences and non ASCII bytes are skipped.
+pub fn keys(input: impl BufRead) -> impl Iterator<Item = io::Result<Key>> {
+    let mut bytes = input.bytes().peekable();
+
+    std::iter::from_fn(move || loop {
+        let byte = match bytes.next()? {
+            Ok(byte) => byte,
+            Err(e) => return Some(Err(e)),
+        };
+
+        match byte {
+            0x1B => {
+                if bytes.next_if(|b| matches!(b, Ok(b'['))).is_none() {
+                    return Some(Ok(Key::Esc));
+                }
+                let key = match bytes.next() {
+                    Some(Ok(b'A')) => Key::Up,
+                    Some(Ok(b'B')) => Key::Down,
+                    Some(Ok(b'C')) => Key::Right,
+                    Some(Ok(b'D')) => Key::Left,
+                    _ => continue,
+                };
+                return Some(Ok(key));
+            }
+            byte if byte.is_ascii() => return Some(Ok(Key::Char(byte as char))),
+            _ => {}
+        }
+    })
+}
+
+// While alive the terminal passes each key as it's pressed and doesn't echo
+// them. Ctrl-C arrives as the byte 3 instead of stopping the program, so
+// the terminal is restored when this is dropped. Does nothing when stdin
+// isn't a terminal.
+pub struct KeyMode {
+    saved: Option<String>,
+}
+
+impl KeyMode {
+    pub fn enable() -> Self {
+        if !io::stdin().is_terminal() {
+            return Self { saved: None };
+        }
+
+        let stty = |args: &[&str]| Command::new("stty").args(args).stdin(Stdio::inherit()).output();
+        let saved = stty(&["-g"])
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+        if saved.is_some() {
+            let _ = stty(&["-icanon", "-echo", "-isig", "min", "1"]);
+        }
+        Self { saved }
+    }
+}
+
+impl Drop for KeyMode {
+    fn drop(&mut self) {
+        if let Some(saved) = &self.saved {
+            let _ = Command::new("stty").arg(saved).stdin(Stdio::inherit()).status();
+        }
+    }
+}
+
+
+#[cfg(test)]
+fn sample_frame(grid: &Grid<char>) -> GridFrame<'_, char, CellFn<char>> {
+    GridFrame::new(grid, |(i, j), c| match c {
+        '#' => ('#', Style::Wall),
+        _ if (i, j) == (1, 1) => ('@', Style::Agent),
+        c => (*c, Style::Plain),
+    })
+}
+
+#[test]
+fn test_render() {
+    let grid = Grid::from_chars("###\n#..\n###");
+    let frame = sample_frame(&grid).caption(String::from("step 1"));
+
+    assert_eq!(render(&frame, &Theme::PLAIN), "step 1\n###\n#@.\n###\n");
+}
+
+#[test]
+fn test_theme_paint() {
+    colored::control::set_override(true);
+    assert_eq!(Theme::PLAIN.paint('#', Style::Wall), "#");
+    assert_eq!(Theme::DEFAULT.paint('#', Style::Wall), "#".blue().to_string());
+    assert_eq!(Theme::DEFAULT.paint('.', Style::Plain), ".");
+}
+
+#[test]
+fn test_player_to_file() {
+    let path = std::env::temp_dir().join("aoc-2024-viz-test.txt");
+    let grid = Grid::from_chars("#.\n.#");
+
+    let mut player = Player::to_file(&path).unwrap();
+    for step in 0 .. 2 {
+        let frame = sample_frame(&grid).caption(format!("step {step}"));
+        assert!(player.show(&frame).unwrap());
+    }
+    assert_eq!(player.frames(), 2);
+    drop(player);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text, "step 0\n#.\n.#\n\nstep 1\n#.\n.#\n");
+}
+
+#[test]
+fn test_keys() {
+    let input: &[u8] = b"wA\x1b[A\x1b[D\x1b[C\x1b[B\x1b[Zq\xc3\xa9";
+    let keys: Vec<_> = keys(input).map(Result::unwrap).collect();
+    assert_eq!(keys, vec![
+        Key::Char('w'), Key::Char('A'), Key::Up, Key::Left, Key::Right, Key::Down, Key::Char('q'),
+    ]);
+}
+
+#[test]
+fn test_keys_esc() {
+    // A bare Esc keeps the keys after it
+    let input: &[u8] = b"\x1bw\x1b\x1bd\x1b";
+    let keys: Vec<_> = keys(input).map(Result::unwrap).collect();
+    assert_eq!(keys, vec![Key::Esc, Key::Char('w'), Key::Esc, Key::Esc, Key::Char('d'), Key::Esc]);
+}